@@ -1,5 +1,6 @@
 use node;
 use tree;
+use schedule;
 
 ///A public type which makes it easier to specifie a tree type
 pub type TreeType = tree::Tree<DefaultContent, Jobs, SceneAttribute>;
@@ -17,11 +18,11 @@ pub enum DefaultContent {
 #[derive(Clone)]
 pub struct DefaultComparer {
     //if position should be compared this can be Some() else it will be ignored
-    position: Option<[f32; 3]>,
+    pub position: Option<[f32; 3]>,
     //if rotation should be compared this can be Some() else it will be ignored
-    rotation: Option<[f32; 3]>,
+    pub rotation: Option<[f32; 3]>,
     //if scale should be compared this can be Some() else it will be ignored
-    scale: Option<f32>,
+    pub scale: Option<f32>,
 }
 
 ///Some `DefaultContent` specific funtions
@@ -100,6 +101,29 @@ pub enum Jobs {
     Scale(f32),
 }
 
+///All jobs are additive, so a part of a job is the same job with its values scaled down.
+impl schedule::JobFraction for Jobs{
+    fn fraction(&self, fraction: f32) -> Self{
+        match self{
+            &Jobs::Translate(t) => Jobs::Translate([t[0] * fraction, t[1] * fraction, t[2] * fraction]),
+            &Jobs::Rotate(r) => Jobs::Rotate([r[0] * fraction, r[1] * fraction, r[2] * fraction]),
+            &Jobs::Scale(s) => Jobs::Scale(s * fraction),
+        }
+    }
+}
+
+///Two jobs of the same kind add up, so they can be executed as one.
+impl node::JobMerge for Jobs{
+    fn merge(&self, next: &Self) -> Option<Self>{
+        match (self, next){
+            (&Jobs::Translate(a), &Jobs::Translate(b)) => Some(Jobs::Translate([a[0] + b[0], a[1] + b[1], a[2] + b[2]])),
+            (&Jobs::Rotate(a), &Jobs::Rotate(b)) => Some(Jobs::Rotate([a[0] + b[0], a[1] + b[1], a[2] + b[2]])),
+            (&Jobs::Scale(a), &Jobs::Scale(b)) => Some(Jobs::Scale(a + b)),
+            _ => None,
+        }
+    }
+}
+
 ///Some example attribte a node can have
 #[derive(Clone)]
 pub struct SceneAttribute {
@@ -18,6 +18,9 @@ use std::time::Instant;
 
 type NodeType = Node<game_tree::DefaultContent, game_tree::Jobs, game_tree::SceneAttribute>;
 
+///How often the tree and the arena tree are compared
+const RUNS: u32 = 1000;
+
 
 fn main(){
     //We'll do 4 level with configurable amounts of items. We then get a single item and merge a small
@@ -38,35 +41,38 @@ fn main(){
     for one in 0..levels[0]{
 
         let mut one_node = game_tree::DefaultContent::Mesh(one.to_string() + "_mesh");
-        let one_node_name = tree.add_at_root(one_node, None).unwrap();
-        match tree.get_node(&one_node_name){
+        let one_node_id = tree.add_at_root(one_node, None).unwrap();
+        match tree.get_mut(one_node_id){
             Some(nod) => nod.set_controller(game_tree::MeshController{}),
             None => {},
         }
 
+        let one_node_name = tree.name_of(one_node_id).unwrap().to_string();
         name = one_node_name.clone();
 
         for two in 0..levels[1]{
             let two_node = game_tree::DefaultContent::Mesh(two.to_string() + "_mesh");
-            let two_node_name = tree.add(two_node, one_node_name.clone(), None).unwrap();
+            let two_node_id = tree.add(two_node, one_node_name.clone(), None).unwrap();
+            let two_node_name = tree.name_of(two_node_id).unwrap().to_string();
 
             for three in 0..levels[2]{
                 let three_node = game_tree::DefaultContent::Mesh(three.to_string() + "_mesh");
-                let three_node_name = tree.add(three_node, two_node_name.clone(), None).unwrap();
+                let three_node_id = tree.add(three_node, two_node_name.clone(), None).unwrap();
+                let three_node_name = tree.name_of(three_node_id).unwrap().to_string();
 
                 for four in 0..levels[3]{
                     let four_node = game_tree::DefaultContent::Mesh(four.to_string() + "_mesh");
-                    let four_node_name = tree.add(four_node, three_node_name.clone(), None).unwrap();
+                    let four_node_id = tree.add(four_node, three_node_name.clone(), None).unwrap();
                 }
             }
         }
     }
 
-    println!("Needed {}ms to fill tree with {} nodes!", time.elapsed().subsec_nanos() as f32 / 1_000_000.0, tree.registry.len());
+    println!("Needed {}ms to fill tree with {} nodes!", time.elapsed().as_secs_f32() * 1000.0, tree.registry.len());
     time = Instant::now();
     //tree.print_tree();
     let _ = tree.get_node("Teddy");
-    println!("Needed {}ms to get a non existent node!", time.elapsed().subsec_nanos() as f32 / 1_000_000.0);
+    println!("Needed {}ms to get a non existent node!", time.elapsed().as_secs_f32() * 1000.0);
 
     tree.update();
     tree.get_node(&name).unwrap().get_attrib_mut().scale = 10.0;
@@ -98,12 +104,38 @@ fn main(){
 
     let _ = tree.join(&new_tree, "RootNode");
 
-    println!("Needed {}ms to merge tree! now has {} nodes", time.elapsed().subsec_nanos() as f32 / 1_000_000.0, tree.registry.len());
-    time = Instant::now();
-
+    println!("Needed {}ms to merge tree! now has {} nodes", time.elapsed().as_secs_f32() * 1000.0, tree.registry.len());
 
+    //Now compare the recursive tree to the arena backed one, both contain the same nodes. A single
+    // run is too short to be measured reliably, so every step is repeated and the average is printed.
+    let mut arena_tree = arena::ArenaTree::from_node(tree.root_node.clone()).unwrap();
+    let names: Vec<String> = tree.registry.keys().cloned().collect();
 
+    time = Instant::now();
+    for _ in 0..RUNS{
+        for node_name in names.iter(){
+            let _ = tree.get_node(node_name);
+        }
+    }
+    println!("Needed {}ms to get all {} nodes of the tree!", time.elapsed().as_secs_f32() * 1000.0 / RUNS as f32, names.len());
 
+    time = Instant::now();
+    for _ in 0..RUNS{
+        for node_name in names.iter(){
+            let _ = arena_tree.get_node(node_name);
+        }
+    }
+    println!("Needed {}ms to get all {} nodes of the arena tree!", time.elapsed().as_secs_f32() * 1000.0 / RUNS as f32, arena_tree.len());
 
+    time = Instant::now();
+    for _ in 0..RUNS{
+        tree.update();
+    }
+    println!("Needed {}ms to update the tree!", time.elapsed().as_secs_f32() * 1000.0 / RUNS as f32);
 
+    time = Instant::now();
+    for _ in 0..RUNS{
+        arena_tree.update();
+    }
+    println!("Needed {}ms to update the arena tree!", time.elapsed().as_secs_f32() * 1000.0 / RUNS as f32);
 }
@@ -14,10 +14,11 @@ fn main(){
     let one_things = vec!["One_One", "One_Two.dot", "One_Three.rold"];
     let mut last_name = String::from("ted");
     for thing in one_things.iter(){
-        last_name = one_tree.add_at_root(
+        let last_id = one_tree.add_at_root(
             game_tree::DefaultContent::Mesh(thing.to_string()),
             Some(game_tree::SceneAttribute::default())
         ).expect("failed to add new node");
+        last_name = one_tree.name_of(last_id).unwrap().to_string();
     }
 
     one_tree.add(
@@ -0,0 +1,454 @@
+use std::collections::HashMap;
+use std::mem;
+use std::borrow::Cow;
+use std::sync::Arc;
+
+use node;
+use naming;
+use id::NodeId;
+use tree::NodeErrors;
+use time;
+
+///A node of an `ArenaTree` together with the indices of its parent and children.
+#[derive(Clone)]
+struct ArenaEntry<T: node::NodeContent + Clone, J: Clone, A: node::Attribute<J> + Clone>{
+    node: node::Node<T, J, A>,
+    ///Index of the parent, `None` for the root node
+    parent: Option<usize>,
+    ///Indices of all children
+    children: Vec<usize>,
+}
+
+///One slot of the arena. A slot is reused after its node was removed, but with a new generation.
+#[derive(Clone)]
+struct ArenaSlot<T: node::NodeContent + Clone, J: Clone, A: node::Attribute<J> + Clone>{
+    generation: u32,
+    entry: Option<ArenaEntry<T, J, A>>,
+}
+
+///An alternative to `tree::Tree` which stores all nodes in one contiguous arena instead of
+/// letting every node own its children.
+///
+/// The hierarchy is described by parent and child indices, so getting a node by its `NodeId` or name
+/// doesn't have to walk a path. `update()` walks the nodes linearly in memory, but visits every node,
+/// while `tree::Tree::update()` skips the branches which have nothing to do. Run the `speed` example to
+/// compare both for a tree.
+///
+/// The `node::Node`s stored in the arena never have children of their own, a `NodeController`
+/// therefore only sees the node it is attached to.
+pub struct ArenaTree<T: node::NodeContent + Clone, J: Clone, A: node::Attribute<J> + Clone>{
+    ///The name of this tree, derived from the name of the root node.
+    pub name: String,
+    slots: Vec<ArenaSlot<T, J, A>>,
+    ///Indices of all free slots
+    free: Vec<usize>,
+    ///Slot index of every node, keyed by the node name
+    names: HashMap<String, usize>,
+    ///Slot index of the root node
+    root: usize,
+    ///All used slot indices, ordered such that every parent comes before its children. This is the
+    /// order in which nodes are updated. It's rebuild lazily after the hierarchy changed.
+    update_order: Vec<usize>,
+    order_is_valid: bool,
+    ///Tells the tree how much time passed since the last update.
+    clock: time::TreeClock,
+    ///Turns the time between two updates into the delta time of the nodes
+    time: time::TimeStep,
+    ///Decides which name a new node gets
+    naming_policy: Arc<dyn naming::NamingPolicy + Send + Sync>,
+    ///Merges consecutive jobs before they are executed, `None` until merging is enabled
+    job_merge: Option<node::MergeFn<J>>,
+}
+
+///Every clone gets its own copy of the clock, like the clones of a `tree::Tree`.
+impl<T: node::NodeContent + Clone, J: Clone, A: node::Attribute<J> + Clone> Clone for ArenaTree<T, J, A> {
+    fn clone(&self) -> Self{
+        ArenaTree{
+            name: self.name.clone(),
+            slots: self.slots.clone(),
+            free: self.free.clone(),
+            names: self.names.clone(),
+            root: self.root,
+            update_order: self.update_order.clone(),
+            order_is_valid: self.order_is_valid,
+            clock: time::clone_clock(&self.clock),
+            time: self.time.clone(),
+            naming_policy: self.naming_policy.clone(),
+            job_merge: self.job_merge,
+        }
+    }
+}
+
+impl<T: node::NodeContent + Clone, J: Clone, A: node::Attribute<J> + Clone> ArenaTree<T, J, A> {
+
+    ///Creates a new arena tree with a `root` node with set `attributes`
+    pub fn new(root: T, root_attributes: A) -> Self{
+        ArenaTree::from_node(node::Node::new(root, root_attributes))
+            .expect("a single node always fits into an empty arena")
+    }
+
+    ///Creates a new arena tree with `root` as its root node. All children of `root` are moved into the
+    /// arena as well, so `ArenaTree::from_node(tree.root_node.clone())` copies a whole `tree::Tree`.
+    /// Returns an error if a child has no valid name, or if two nodes of different branches have the same name,
+    /// since the arena finds nodes by unique names.
+    pub fn from_node(root: node::Node<T, J, A>) -> Result<Self, NodeErrors>{
+        let mut arena = ArenaTree{
+            name: root.get_name(),
+            slots: Vec::new(),
+            free: Vec::new(),
+            names: HashMap::new(),
+            root: 0,
+            update_order: Vec::new(),
+            order_is_valid: false,
+            clock: time::tree_clock(time::RealClock::new()),
+            time: time::TimeStep::new(),
+            naming_policy: Arc::new(naming::CounterSuffix::new()),
+            job_merge: None,
+        };
+
+        arena.root = arena.insert_subtree(root, None)?;
+        Ok(arena)
+    }
+
+    ///Sets the `policy` which decides under which name new nodes are added to this tree.
+    /// The default is `naming::CounterSuffix`.
+    pub fn set_naming_policy<P>(&mut self, policy: P) where P: naming::NamingPolicy + Send + Sync + 'static{
+        self.naming_policy = Arc::new(policy);
+    }
+
+    ///Returns the id of the root node
+    pub fn root(&self) -> NodeId{
+        NodeId::new(self.root, self.slots[self.root].generation)
+    }
+
+    ///Same as `add()` but the `new_child` will be added to the root node
+    pub fn add_at_root(&mut self, new_child: T, attributes: Option<A>) -> Result<NodeId, NodeErrors>{
+        let root = self.root();
+        self.add(new_child, root, attributes)
+    }
+
+    ///Adds a `new_child` to the `parent` node with `Some(attributes)` set
+    /// (or the default attributes if None is supplied).
+    /// Returns the id of the new node as `Ok(id)` or an `Err(e)` if something went wrong.
+    pub fn add(&mut self, new_child: T, parent: NodeId, attributes: Option<A>) -> Result<NodeId, NodeErrors>{
+        let parent_index = match self.index_of(parent){
+            Some(index) => index,
+            None => return Err(NodeErrors::NoNodeFound(String::from("The parent id is stale"))),
+        };
+
+        let unique_name = {
+            let names = &self.names;
+            self.naming_policy.unique_name(&new_child.get_name(), &|name| names.contains_key(name))?
+        };
+        let attributes = match attributes{
+            Some(atr) => atr,
+            None => A::default(),
+        };
+        let mut new_node = node::Node::new(new_child, attributes);
+        new_node.set_name(unique_name);
+
+        //The policy could be a custom one, inserting checks the name again
+        let index = self.insert_subtree(new_node, Some(parent_index))?;
+        Ok(NodeId::new(index, self.slots[index].generation))
+    }
+
+    ///Removes the node `id` points to and all of its children from the arena. Returns the removed
+    /// node with all its children re-attached, so it can be used with `tree::Tree::from_node()`,
+    /// or an `Err(e)` if the id is stale or points to the root node.
+    pub fn remove(&mut self, id: NodeId) -> Result<node::Node<T, J, A>, NodeErrors>{
+        let index = match self.index_of(id){
+            Some(index) => index,
+            None => return Err(NodeErrors::NoNodeFound(String::from("The id is stale"))),
+        };
+        if index == self.root{
+            return Err(NodeErrors::IsRootNode(String::from("Can't remove the root node ") + &self.name));
+        }
+
+        //detach from the parent, then take the whole subtree out of the arena
+        if let Some(parent) = self.entry(index).parent{
+            if let Some(ref mut parent_entry) = self.slots[parent].entry{
+                parent_entry.children.retain(|child| *child != index);
+            }
+        }
+        self.order_is_valid = false;
+
+        Ok(self.take_subtree(index))
+    }
+
+    ///Returns a reference to the node `id` points to, or `None` if the id is stale.
+    pub fn get(&self, id: NodeId) -> Option<&node::Node<T, J, A>>{
+        let index = self.index_of(id)?;
+        Some(&self.entry(index).node)
+    }
+
+    ///Returns a mutable reference to the node `id` points to, or `None` if the id is stale.
+    pub fn get_mut(&mut self, id: NodeId) -> Option<&mut node::Node<T, J, A>>{
+        let index = self.index_of(id)?;
+        Some(&mut self.entry_mut(index).node)
+    }
+
+    ///Returns a node with this `name`
+    pub fn get_node(&mut self, name: &str) -> Option<&mut node::Node<T, J, A>>{
+        let index = *self.names.get(name)?;
+        Some(&mut self.entry_mut(index).node)
+    }
+
+    ///Returns the id of the node with this `name`
+    pub fn id_of(&self, name: &str) -> Option<NodeId>{
+        let index = *self.names.get(name)?;
+        Some(NodeId::new(index, self.slots[index].generation))
+    }
+
+    ///Returns the name of the node `id` points to, or `None` if the id is stale.
+    pub fn name_of(&self, id: NodeId) -> Option<String>{
+        self.get(id).map(|node| node.get_name())
+    }
+
+    ///Returns true if this tree contains a node with this name
+    pub fn has_node(&self, name: &str) -> bool{
+        self.names.contains_key(name)
+    }
+
+    ///Returns true if `id` still points to a node of this tree
+    pub fn contains(&self, id: NodeId) -> bool{
+        self.index_of(id).is_some()
+    }
+
+    ///Returns the id of the parent of `id`, or `None` for the root node and stale ids.
+    pub fn parent_of(&self, id: NodeId) -> Option<NodeId>{
+        let parent = self.entry(self.index_of(id)?).parent?;
+        Some(NodeId::new(parent, self.slots[parent].generation))
+    }
+
+    ///Returns the ids of all children of `id`. The list is empty for stale ids.
+    pub fn children_of(&self, id: NodeId) -> Vec<NodeId>{
+        match self.index_of(id){
+            Some(index) => self.entry(index).children.iter()
+                .map(|child| NodeId::new(*child, self.slots[*child].generation))
+                .collect(),
+            None => Vec::new(),
+        }
+    }
+
+    ///Returns the number of nodes in this tree
+    pub fn len(&self) -> usize{
+        self.names.len()
+    }
+
+    ///Returns true if there are no nodes in this tree. Since there is always a root node this is
+    /// never the case, it's only here for completeness.
+    pub fn is_empty(&self) -> bool{
+        self.names.is_empty()
+    }
+
+    ///Sets the `clock` which tells the tree how much time passed since the last update. The default
+    /// is a `time::RealClock`. Every clone of this tree gets its own copy of the clock.
+    pub fn set_clock<C>(&mut self, clock: C) where C: time::Clock + Send + 'static{
+        self.clock = time::tree_clock(clock);
+    }
+
+    ///Lets every node merge consecutive jobs before executing them, see `tree::Tree::enable_job_merging()`.
+    pub fn enable_job_merging(&mut self) where J: node::JobMerge{
+        self.job_merge = Some(J::merge);
+    }
+
+    ///Executes every job on its own again, see `enable_job_merging()`.
+    pub fn disable_job_merging(&mut self){
+        self.job_merge = None;
+    }
+
+    ///Returns the time step, which controls the time scale, pausing and fixed steps of `update()`.
+    pub fn time_step(&self) -> &time::TimeStep{
+        &self.time
+    }
+
+    ///Returns the time step mutable, for instance to pause the tree or to switch to fixed steps.
+    pub fn time_step_mut(&mut self) -> &mut time::TimeStep{
+        &mut self.time
+    }
+
+    ///Updates the whole tree, see `tree::Tree::update()`. Every node is updated after its parent, but
+    /// the nodes are visited in the order they are stored in the arena instead of recursing through
+    /// the children.
+    pub fn update(&mut self){
+        let real_delta = self.clock.lock().expect("failed to lock clock").tick();
+        self.update_with_delta(real_delta);
+    }
+
+    ///Updates the whole tree as if `real_delta` seconds passed since the last update, see
+    /// `tree::Tree::update_with_delta()`.
+    pub fn update_with_delta(&mut self, real_delta: f32){
+        if !self.order_is_valid{
+            self.rebuild_update_order();
+        }
+
+        for delta in self.time.advance(real_delta){
+            self.update_nodes(delta);
+        }
+    }
+
+    ///Updates every node once with `delta`
+    fn update_nodes(&mut self, delta: f32){
+        let context = node::UpdateContext{
+            delta,
+            merge: self.job_merge,
+        };
+
+        //The jobs every node passes down to its children, indexed like the slots
+        let mut outgoing_jobs: Vec<node::ChildJobs<J>> = Vec::new();
+        outgoing_jobs.resize_with(self.slots.len(), node::ChildJobs::new);
+
+        for index in self.update_order.iter(){
+            let entry = match self.slots[*index].entry{
+                Some(ref mut entry) => entry,
+                None => continue,
+            };
+
+            let child_jobs = {
+                let parent_jobs = match entry.parent{
+                    Some(parent) => outgoing_jobs[parent].for_child(&entry.node),
+                    None => Cow::Owned(Vec::new()),
+                };
+                entry.node.update_self(&context, &parent_jobs)
+            };
+            //leaves don't need to store anything
+            if !entry.children.is_empty(){
+                outgoing_jobs[*index] = child_jobs;
+            }
+        }
+
+        //the bottom-up phase, the reversed order visits every node after all of its children
+        for index in self.update_order.iter().rev(){
+            let children = match self.slots[*index].entry{
+                Some(ref entry) => entry.children.clone(),
+                None => continue,
+            };
+
+            let mut incoming = Vec::new();
+            for child in children.iter(){
+                if let Some(ref mut child_entry) = self.slots[*child].entry{
+                    incoming.append(&mut child_entry.node.take_upward());
+                }
+            }
+
+            if let Some(ref mut entry) = self.slots[*index].entry{
+                entry.node.update_upward(delta, incoming);
+            }
+        }
+        //nothing is above the root node
+        let root = self.root;
+        self.entry_mut(root).node.take_upward();
+    }
+
+    ///Prints a debug tree of the things in this tree
+    pub fn print_tree(&self){
+        self.print_entry(self.root, 0);
+        println!("There are {} nodes in the tree!", self.len());
+    }
+
+    fn print_entry(&self, index: usize, lvl: i32){
+        let entry = self.entry(index);
+        for _ in 0..lvl{
+            print!("\t");
+        }
+        println!("{}", entry.node.get_name());
+        entry.node.get_attrib().print_atr(lvl);
+
+        for child in entry.children.iter(){
+            self.print_entry(*child, lvl + 1);
+        }
+    }
+
+    ///Returns the slot index of `id` if it isn't stale
+    fn index_of(&self, id: NodeId) -> Option<usize>{
+        let slot = self.slots.get(id.index())?;
+        if slot.generation == id.generation() && slot.entry.is_some(){
+            Some(id.index())
+        }else{
+            None
+        }
+    }
+
+    ///Returns the entry at a used slot `index`
+    fn entry(&self, index: usize) -> &ArenaEntry<T, J, A>{
+        self.slots[index].entry.as_ref().expect("used an empty arena slot")
+    }
+
+    ///Returns the entry at a used slot `index`
+    fn entry_mut(&mut self, index: usize) -> &mut ArenaEntry<T, J, A>{
+        self.slots[index].entry.as_mut().expect("used an empty arena slot")
+    }
+
+    ///Moves `node` and all of its children into the arena below `parent`. Returns the slot index
+    /// of `node`.
+    fn insert_subtree(&mut self, mut node: node::Node<T, J, A>, parent: Option<usize>) -> Result<usize, NodeErrors>{
+        let children = mem::take(node.get_children_mut());
+        let name = node.get_name();
+        //like in `tree::Tree` the root node can be called anything
+        if parent.is_some(){
+            let names = &self.names;
+            naming::check_new_name(&name, &|taken| names.contains_key(taken))?;
+        }
+
+        let entry = ArenaEntry{
+            node,
+            parent,
+            children: Vec::new(),
+        };
+        let index = match self.free.pop(){
+            Some(index) => {
+                self.slots[index].entry = Some(entry);
+                index
+            },
+            None => {
+                self.slots.push(ArenaSlot{
+                    generation: 0,
+                    entry: Some(entry),
+                });
+                self.slots.len() - 1
+            }
+        };
+        self.names.insert(name, index);
+
+        if let Some(parent_index) = parent{
+            self.entry_mut(parent_index).children.push(index);
+        }
+        self.order_is_valid = false;
+
+        for (_, child) in children.into_iter(){
+            self.insert_subtree(child, Some(index))?;
+        }
+
+        Ok(index)
+    }
+
+    ///Takes the node at `index` and all of its children out of the arena and rebuilds the recursive
+    /// node structure. The reference from the parent is not touched.
+    fn take_subtree(&mut self, index: usize) -> node::Node<T, J, A>{
+        let entry = {
+            let slot = &mut self.slots[index];
+            slot.generation = slot.generation.wrapping_add(1);
+            slot.entry.take().expect("used an empty arena slot")
+        };
+        self.free.push(index);
+        self.names.remove(&entry.node.get_name());
+
+        let mut node = entry.node;
+        for child in entry.children.into_iter(){
+            let child_node = self.take_subtree(child);
+            node.get_children_mut().insert(child_node.get_name(), child_node);
+        }
+        node
+    }
+
+    ///Collects all nodes in an order where every parent comes before its children.
+    fn rebuild_update_order(&mut self){
+        self.update_order.clear();
+        let mut stack = vec![self.root];
+        while let Some(index) = stack.pop(){
+            self.update_order.push(index);
+            stack.extend(self.entry(index).children.iter().rev());
+        }
+        self.order_is_valid = true;
+    }
+}
@@ -0,0 +1,122 @@
+use std::collections::BTreeMap;
+
+///A stable handle to a node of a tree. In contrast to a name, an id is never reused for another
+/// node: if the node is removed, the id becomes stale and every lookup with it fails, even if a new
+/// node with the same name is added later.
+///
+/// An id is only meaningful for the tree which returned it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct NodeId{
+    ///Index of the slot this id points to
+    index: usize,
+    ///Generation of the slot at the time this id was created
+    generation: u32,
+}
+
+impl NodeId{
+    ///Creates an id for the slot at `index` in its `generation`.
+    pub(crate) fn new(index: usize, generation: u32) -> Self{
+        NodeId{
+            index,
+            generation,
+        }
+    }
+
+    ///Returns the index of the slot this id points to
+    pub fn index(&self) -> usize{
+        self.index
+    }
+
+    ///Returns the generation of the slot this id was created for. The generation increases every
+    /// time the slot is reused.
+    pub fn generation(&self) -> u32{
+        self.generation
+    }
+}
+
+///A single slot of an `IdTable`.
+#[derive(Clone, Debug)]
+struct Slot{
+    generation: u32,
+    ///Name of the node using this slot, `None` if the slot is free
+    name: Option<String>,
+}
+
+///Hands out `NodeId`s for node names and maps them back to the names.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct IdTable{
+    slots: Vec<Slot>,
+    ///Indices of all free slots
+    free: Vec<usize>,
+    ///The id of every node, keyed by the node name
+    by_name: BTreeMap<String, NodeId>,
+}
+
+impl IdTable{
+    ///Creates an empty table
+    pub(crate) fn new() -> Self{
+        IdTable{
+            slots: Vec::new(),
+            free: Vec::new(),
+            by_name: BTreeMap::new(),
+        }
+    }
+
+    ///Creates a new id for `name` and returns it. Free slots are reused with a new generation.
+    pub(crate) fn insert(&mut self, name: String) -> NodeId{
+        let id = match self.free.pop(){
+            Some(index) => {
+                let slot = &mut self.slots[index];
+                slot.name = Some(name.clone());
+                NodeId{
+                    index,
+                    generation: slot.generation,
+                }
+            },
+            None => {
+                self.slots.push(Slot{
+                    generation: 0,
+                    name: Some(name.clone()),
+                });
+                NodeId{
+                    index: self.slots.len() - 1,
+                    generation: 0,
+                }
+            }
+        };
+
+        self.by_name.insert(name, id);
+        id
+    }
+
+    ///Frees the id of `name`. Every id handed out for it becomes stale.
+    pub(crate) fn remove(&mut self, name: &str) -> Option<NodeId>{
+        let id = self.by_name.remove(name)?;
+        let slot = &mut self.slots[id.index];
+        slot.name = None;
+        slot.generation = slot.generation.wrapping_add(1);
+        self.free.push(id.index);
+        Some(id)
+    }
+
+    ///Moves the id of `old` to `new`, the id itself stays valid.
+    pub(crate) fn rename(&mut self, old: &str, new: &str){
+        if let Some(id) = self.by_name.remove(old){
+            self.slots[id.index].name = Some(new.to_string());
+            self.by_name.insert(new.to_string(), id);
+        }
+    }
+
+    ///Returns the name of the node `id` points to, or `None` if the id is stale
+    pub(crate) fn name_of(&self, id: NodeId) -> Option<&str>{
+        match self.slots.get(id.index){
+            Some(slot) if slot.generation == id.generation => slot.name.as_deref(),
+            _ => None,
+        }
+    }
+
+    ///Returns the id of the node called `name`
+    pub(crate) fn id_of(&self, name: &str) -> Option<NodeId>{
+        self.by_name.get(name).cloned()
+    }
+}
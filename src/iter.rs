@@ -0,0 +1,223 @@
+use std::collections::{btree_map, VecDeque};
+
+use node;
+use path::NodePath;
+
+///Walks a node and all of its children depth first. Every node is returned before its children,
+/// siblings are returned ordered by name.
+///
+/// Yields the path of every node (relative to the node the walk started at), its depth and the node.
+pub struct Iter<'a, T: 'a + node::NodeContent + Clone, J: 'a + Clone, A: 'a + node::Attribute<J> + Clone>{
+    stack: Vec<(NodePath, usize, &'a node::Node<T, J, A>)>,
+}
+
+impl<'a, T: node::NodeContent + Clone, J: Clone, A: node::Attribute<J> + Clone> Iter<'a, T, J, A>{
+    ///Creates an iterator which starts at `start`
+    pub(crate) fn new(start: &'a node::Node<T, J, A>) -> Self{
+        Iter{
+            stack: vec![(NodePath::root(), 0, start)],
+        }
+    }
+}
+
+impl<'a, T: node::NodeContent + Clone, J: Clone, A: node::Attribute<J> + Clone> Iterator for Iter<'a, T, J, A>{
+    type Item = (NodePath, usize, &'a node::Node<T, J, A>);
+
+    fn next(&mut self) -> Option<Self::Item>{
+        let (path, depth, current) = self.stack.pop()?;
+        //push reversed, so the first child is popped first
+        for (child_name, child) in current.get_children().iter().rev(){
+            self.stack.push((path.join(child_name), depth + 1, child));
+        }
+        Some((path, depth, current))
+    }
+}
+
+///Walks a node and all of its children breadth first: first the node, then all of its children,
+/// then all of their children and so on.
+///
+/// Yields the same items as `Iter`.
+pub struct BfsIter<'a, T: 'a + node::NodeContent + Clone, J: 'a + Clone, A: 'a + node::Attribute<J> + Clone>{
+    queue: VecDeque<(NodePath, usize, &'a node::Node<T, J, A>)>,
+}
+
+impl<'a, T: node::NodeContent + Clone, J: Clone, A: node::Attribute<J> + Clone> BfsIter<'a, T, J, A>{
+    ///Creates an iterator which starts at `start`
+    pub(crate) fn new(start: &'a node::Node<T, J, A>) -> Self{
+        let mut queue = VecDeque::new();
+        queue.push_back((NodePath::root(), 0, start));
+        BfsIter{
+            queue,
+        }
+    }
+}
+
+impl<'a, T: node::NodeContent + Clone, J: Clone, A: node::Attribute<J> + Clone> Iterator for BfsIter<'a, T, J, A>{
+    type Item = (NodePath, usize, &'a node::Node<T, J, A>);
+
+    fn next(&mut self) -> Option<Self::Item>{
+        let (path, depth, current) = self.queue.pop_front()?;
+        for (child_name, child) in current.get_children().iter(){
+            self.queue.push_back((path.join(child_name), depth + 1, child));
+        }
+        Some((path, depth, current))
+    }
+}
+
+///A node of the `PostOrderIter` stack together with the children which haven't been walked yet.
+type PostOrderEntry<'a, T, J, A> = (NodePath, usize, &'a node::Node<T, J, A>, btree_map::Iter<'a, String, node::Node<T, J, A>>);
+
+///Walks a node and all of its children depth first, but returns every node after all of its children.
+///
+/// Yields the same items as `Iter`.
+pub struct PostOrderIter<'a, T: 'a + node::NodeContent + Clone, J: 'a + Clone, A: 'a + node::Attribute<J> + Clone>{
+    stack: Vec<PostOrderEntry<'a, T, J, A>>,
+}
+
+impl<'a, T: node::NodeContent + Clone, J: Clone, A: node::Attribute<J> + Clone> PostOrderIter<'a, T, J, A>{
+    ///Creates an iterator which starts at `start`
+    pub(crate) fn new(start: &'a node::Node<T, J, A>) -> Self{
+        PostOrderIter{
+            stack: vec![(NodePath::root(), 0, start, start.get_children().iter())],
+        }
+    }
+}
+
+impl<'a, T: node::NodeContent + Clone, J: Clone, A: node::Attribute<J> + Clone> Iterator for PostOrderIter<'a, T, J, A>{
+    type Item = (NodePath, usize, &'a node::Node<T, J, A>);
+
+    fn next(&mut self) -> Option<Self::Item>{
+        loop {
+            let next_child = {
+                let &mut (ref path, depth, _, ref mut children) = self.stack.last_mut()?;
+                children.next().map(|(child_name, child)| (path.join(child_name), depth + 1, child))
+            };
+
+            match next_child{
+                //walk down until we reach a node without unvisited children
+                Some((child_path, child_depth, child)) => {
+                    self.stack.push((child_path, child_depth, child, child.get_children().iter()));
+                },
+                None => {
+                    let (path, depth, current, _) = self.stack.pop()?;
+                    return Some((path, depth, current));
+                }
+            }
+        }
+    }
+}
+
+///Mutable access to everything of a node except its children. This is what `IterMut` yields,
+/// since the children of a node are walked by the iterator itself.
+pub struct NodeMut<'a, T: 'a, J: 'a, A: 'a>{
+    pub(crate) name: &'a str,
+    pub(crate) value: &'a mut T,
+    pub(crate) jobs: &'a mut Vec<node::CarriedJob<J>>,
+    pub(crate) attributes: &'a mut A,
+}
+
+impl<'a, T, J, A> NodeMut<'a, T, J, A>{
+    ///Returns the name of the node
+    pub fn get_name(&self) -> &str{
+        self.name
+    }
+
+    ///Returns a reference to the value of the node
+    pub fn get_value(&self) -> &T{
+        self.value
+    }
+
+    ///Returns a mutable reference to the value of the node
+    pub fn get_value_mut(&mut self) -> &mut T{
+        self.value
+    }
+
+    ///Returns the jobs which are queued on the node, in the order they are executed
+    pub fn get_jobs(&self) -> impl Iterator<Item = &J> + '_{
+        self.jobs.iter().map(|carried| &carried.0)
+    }
+
+    ///Adds a job to the node
+    pub fn add_job(&mut self, job: J){
+        self.add_job_with_mode(job, node::JobMode::Propagate);
+    }
+
+    ///Adds a job to the node, see `node::Node::add_job_with_mode()`
+    pub fn add_job_with_mode(&mut self, job: J, mode: node::JobMode){
+        self.jobs.push((job, mode));
+    }
+
+    ///Returns a reference to the current attributes of the node
+    pub fn get_attrib(&self) -> &A{
+        self.attributes
+    }
+
+    ///Returns a mutable reference to the current attributes of the node
+    pub fn get_attrib_mut(&mut self) -> &mut A{
+        self.attributes
+    }
+}
+
+///Same as `Iter`, but yields a `NodeMut` for every node.
+pub struct IterMut<'a, T: 'a + node::NodeContent + Clone, J: 'a + Clone, A: 'a + node::Attribute<J> + Clone>{
+    stack: Vec<(NodePath, usize, &'a mut node::Node<T, J, A>)>,
+}
+
+impl<'a, T: node::NodeContent + Clone, J: Clone, A: node::Attribute<J> + Clone> IterMut<'a, T, J, A>{
+    ///Creates an iterator which starts at `start`
+    pub(crate) fn new(start: &'a mut node::Node<T, J, A>) -> Self{
+        IterMut{
+            stack: vec![(NodePath::root(), 0, start)],
+        }
+    }
+}
+
+impl<'a, T: node::NodeContent + Clone, J: Clone, A: node::Attribute<J> + Clone> Iterator for IterMut<'a, T, J, A>{
+    type Item = (NodePath, usize, NodeMut<'a, T, J, A>);
+
+    fn next(&mut self) -> Option<Self::Item>{
+        let (path, depth, current) = self.stack.pop()?;
+        let (current_mut, children) = current.split_mut();
+        for (child_name, child) in children.iter_mut().rev(){
+            self.stack.push((path.join(child_name), depth + 1, child));
+        }
+        Some((path, depth, current_mut))
+    }
+}
+
+///Walks from a node up to the root node, yielding the name of every ancestor. The node itself is not
+/// yielded, the root node is yielded last.
+pub struct Ancestors{
+    ///The names of the ancestors below the root node, the parent of the node is the last one
+    remaining: Vec<String>,
+    ///The name of the root node, `None` once it was yielded
+    root_name: Option<String>,
+}
+
+impl Ancestors{
+    ///Creates the iterator for the node at `path` in a tree whose root node is called `root_name`.
+    pub(crate) fn new(path: &NodePath, root_name: String) -> Self{
+        match path.parent(){
+            Some(parent) => Ancestors{
+                remaining: parent.segments().to_vec(),
+                root_name: Some(root_name),
+            },
+            //The root node has no ancestors
+            None => Ancestors{
+                remaining: Vec::new(),
+                root_name: None,
+            },
+        }
+    }
+}
+
+impl Iterator for Ancestors{
+    type Item = String;
+
+    fn next(&mut self) -> Option<String>{
+        match self.remaining.pop(){
+            Some(name) => Some(name),
+            None => self.root_name.take(),
+        }
+    }
+}
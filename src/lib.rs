@@ -20,3 +20,22 @@
 pub mod tree;
 ///This module describes
 pub mod node;
+///This module describes the paths which locate nodes within a tree.
+pub mod path;
+///This module describes the clocks which measure the time between two updates, and how this time is
+/// turned into the delta time of the nodes.
+pub mod time;
+///This module describes jobs which are executed after some time, repeatedly or spread over some time.
+pub mod schedule;
+///This module describes the iterators which walk over all nodes of a tree.
+pub mod iter;
+///This module describes visitors which can walk a tree with enter and exit callbacks.
+pub mod visit;
+///This module describes selectors which address groups of nodes by patterns of their names.
+pub mod select;
+///This module describes how a tree finds unique names for new nodes.
+pub mod naming;
+///This module describes the ids which can be used to reference nodes of a tree.
+pub mod id;
+///This module describes a tree which stores its nodes in one continuous arena.
+pub mod arena;
@@ -0,0 +1,127 @@
+use std::collections::BTreeMap;
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use std::sync::Mutex;
+
+use tree::NodeErrors;
+
+///Decides under which name a new node is stored in a tree. The policy is asked every time a node
+/// is added to a tree, including the nodes added while joining two trees.
+///
+/// Besides the policies in this module, every closure with the signature of `unique_name()` can be
+/// used as a policy. Use `custom()` to get the closure's argument types inferred.
+pub trait NamingPolicy {
+    ///Returns the name a node which would like to be called `wanted` should get. `is_taken` returns
+    /// true for every name which is already used in the tree.
+    fn unique_name(&self, wanted: &str, is_taken: &dyn Fn(&str) -> bool) -> Result<String, NodeErrors>;
+}
+
+///Refuses to add a node if its name is already taken.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct FailOnDuplicate;
+
+impl NamingPolicy for FailOnDuplicate{
+    fn unique_name(&self, wanted: &str, is_taken: &dyn Fn(&str) -> bool) -> Result<String, NodeErrors>{
+        if is_taken(wanted){
+            return Err(NodeErrors::NameTaken(
+                String::from("There is already a node called ") + wanted + " in the tree!"
+            ));
+        }
+        Ok(wanted.to_string())
+    }
+}
+
+///Appends `_0`, `_1`, `_2` ... to a taken name until it is unique. This is the default policy
+/// of a tree.
+///
+/// The policy remembers the last number it used for every name, so adding the same name over and
+/// over again does not have to test every number from 0 on again.
+#[derive(Debug, Default)]
+pub struct CounterSuffix{
+    next_number: Mutex<BTreeMap<String, usize>>,
+}
+
+impl CounterSuffix{
+    ///Creates the policy without any remembered numbers.
+    pub fn new() -> Self{
+        CounterSuffix{
+            next_number: Mutex::new(BTreeMap::new()),
+        }
+    }
+}
+
+impl NamingPolicy for CounterSuffix{
+    fn unique_name(&self, wanted: &str, is_taken: &dyn Fn(&str) -> bool) -> Result<String, NodeErrors>{
+        if !is_taken(wanted){
+            return Ok(wanted.to_string());
+        }
+
+        let mut next_numbers = self.next_number.lock().expect("failed to lock naming counter");
+        //The remembered number is only a hint, we still have to check it, since the tree might have
+        // changed since the last time (or might be a different tree).
+        let mut append_number = next_numbers.get(wanted).cloned().unwrap_or(0);
+        while is_taken(&(wanted.to_string() + "_" + &append_number.to_string())){
+            append_number += 1;
+        }
+        next_numbers.insert(wanted.to_string(), append_number + 1);
+
+        Ok(wanted.to_string() + "_" + &append_number.to_string())
+    }
+}
+
+///Appends a random, UUID like suffix of 32 hex digits to a taken name until it is unique.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct RandomSuffix;
+
+impl NamingPolicy for RandomSuffix{
+    fn unique_name(&self, wanted: &str, is_taken: &dyn Fn(&str) -> bool) -> Result<String, NodeErrors>{
+        if !is_taken(wanted){
+            return Ok(wanted.to_string());
+        }
+
+        loop {
+            //Every `RandomState` is seeded with different keys, so hashing nothing gives us a new
+            // random number every time.
+            let high = RandomState::new().build_hasher().finish();
+            let low = RandomState::new().build_hasher().finish();
+            let candidate = format!("{}_{:016x}{:016x}", wanted, high, low);
+            if !is_taken(&candidate){
+                return Ok(candidate);
+            }
+        }
+    }
+}
+
+impl<F> NamingPolicy for F where F: Fn(&str, &dyn Fn(&str) -> bool) -> Result<String, NodeErrors>{
+    fn unique_name(&self, wanted: &str, is_taken: &dyn Fn(&str) -> bool) -> Result<String, NodeErrors>{
+        self(wanted, is_taken)
+    }
+}
+
+///Returns `policy` unchanged. Only helps the compiler to infer the argument types of a closure which
+/// should be used as a `NamingPolicy`.
+pub fn custom<F>(policy: F) -> F where F: Fn(&str, &dyn Fn(&str) -> bool) -> Result<String, NodeErrors>{
+    policy
+}
+
+///Returns true if `name` can be used as a node name. Every name can be stored in a `NodePath`,
+/// but an empty name couldn't be told apart from its parent.
+pub(crate) fn is_valid_name(name: &str) -> bool{
+    !name.is_empty()
+}
+
+///Returns an error if a new node can't be called `name`, because it is no valid name or because
+/// `is_taken` returns true for it. Policies could be custom ones, so every name they return is checked.
+pub(crate) fn check_new_name(name: &str, is_taken: &dyn Fn(&str) -> bool) -> Result<(), NodeErrors>{
+    if !is_valid_name(name){
+        return Err(NodeErrors::InvalidName(
+            String::from("Can't use ") + name + " as a node name!"
+        ));
+    }
+    if is_taken(name){
+        return Err(NodeErrors::NameTaken(
+            String::from("There is already a node called ") + name + " in the tree!"
+        ));
+    }
+    Ok(())
+}
@@ -1,11 +1,28 @@
 use std::collections::BTreeMap;
 use tree;
+use path::NodePath;
+use iter;
+use visit;
+use schedule;
 use std::sync::{Arc, Mutex};
+use std::convert::Infallible;
+use std::borrow::Cow;
+use std::mem;
+#[cfg(feature = "parallel")]
+use std::thread;
 
 ///Can controll behavoir of a node. Gets called via the update function at every update
 /// of that node.
 pub trait NodeController<T,J,A> where  T: NodeContent + Clone, J: Clone, A: Attribute<J> + Clone{
     fn update(&mut self, node: &mut Node<T, J, A>);
+    ///Is called instead of `update()` with the time in seconds since the last update of the node.
+    /// Calls `update()` by default, implement it if the controller needs the delta time.
+    fn update_with_delta(&mut self, node: &mut Node<T, J, A>, _delta: f32){
+        self.update(node);
+    }
+    ///Is called after the children of the node were updated and the jobs they pushed up were executed.
+    /// Jobs for the parent can be pushed with `node.push_up()`. Does nothing by default.
+    fn post_update(&mut self, _node: &mut Node<T, J, A>, _delta: f32){}
 }
 
 
@@ -23,12 +40,156 @@ pub trait Attribute<J: Clone> {
     /// With this way you could call `rotate()` on this object, but pass a `rotate_around_point()`
     /// down to the children.
     fn execute(&mut self, job: &J) -> J;
+    ///Is called instead of `execute()` while updating a node, with the time in seconds since the last
+    /// update. Calls `execute()` by default, implement it if a job depends on the delta time.
+    fn execute_with_delta(&mut self, job: &J, _delta: f32) -> J{
+        self.execute(job)
+    }
+    ///Is called instead of `execute_with_delta()` while updating a node. Executes the `job` on `self` and
+    /// decides what reaches the children: nothing, one or several jobs for all children, or a job for
+    /// some children only. Sends the job returned by `execute_with_delta()` to all children by default.
+    fn propagate(&mut self, job: &J, delta: f32) -> Propagation<J>{
+        Propagation::Forward(self.execute_with_delta(job, delta))
+    }
+    ///Executes a `job` a child pushed up to this node after the child was updated. Returns the job
+    /// which should be pushed further up to the parent, if any. Executes the job and stops it by default.
+    fn execute_upward(&mut self, job: &J, delta: f32) -> Option<J>{
+        self.execute_with_delta(job, delta);
+        None
+    }
     ///Should print the content of self in an readable form.
     fn print_atr(&self, lvl: i32);
     ///Returns true if `self` matches the supplied `attributes`
     fn compare(&self, attributes: &Self::Comparer) -> bool;
 }
 
+///What a node sends to its children after executing a job, see `Attribute::propagate()`.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Propagation<J>{
+    ///The job stops at this node, the children get nothing.
+    Stop,
+    ///Every child gets this job.
+    Forward(J),
+    ///Every child gets all of these jobs, in this order.
+    Fan(Vec<J>),
+    ///Only the children named in the map get their job, all other children get nothing.
+    PerChild(BTreeMap<String, J>),
+}
+
+///Decides which nodes a job added to a node reaches.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum JobMode{
+    ///The job is executed on the node and sent down to all children (recursively). This is what
+    /// `add_job()` does.
+    Propagate,
+    ///The job is only executed on the node itself, the children get nothing.
+    SelfOnly,
+    ///The job is not executed on the node, but sent to all children (recursively) as if it was added to each of them.
+    ChildrenOnly,
+    ///The job is executed on the node and sent down the given number of levels. `Depth(0)` is the same as `SelfOnly`.
+    Depth(usize),
+}
+
+impl JobMode{
+    ///Returns false if a job in this mode skips the node it is added to
+    fn executes_here(&self) -> bool{
+        *self != JobMode::ChildrenOnly
+    }
+
+    ///Returns the mode the children get the job in, or `None` if it isn't sent to them.
+    fn for_children(&self) -> Option<JobMode>{
+        match *self{
+            JobMode::Propagate | JobMode::ChildrenOnly => Some(JobMode::Propagate),
+            JobMode::SelfOnly | JobMode::Depth(0) => None,
+            JobMode::Depth(levels) => Some(JobMode::Depth(levels - 1)),
+        }
+    }
+}
+
+///Jobs which can be merged with the job queued after them, for instance two translations into one.
+/// A tree only merges jobs after `Tree::enable_job_merging()` was called.
+pub trait JobMerge: Sized{
+    ///Returns the job which has the same effect as executing self and then `next`, or `None` if the two
+    /// jobs can't be merged. Only jobs in the same `JobMode` are merged.
+    fn merge(&self, next: &Self) -> Option<Self>;
+}
+
+///Merges two consecutive jobs, see `JobMerge`.
+pub(crate) type MergeFn<J> = fn(&J, &J) -> Option<J>;
+
+///What every node needs to know while being updated, besides the jobs of its parent.
+pub(crate) struct UpdateContext<J>{
+    ///The time in seconds since the last update
+    pub(crate) delta: f32,
+    ///Merges consecutive jobs before they are executed, if the tree enabled it
+    pub(crate) merge: Option<MergeFn<J>>,
+}
+
+///A job on its way down the tree, together with the mode it reaches the next node in.
+pub(crate) type CarriedJob<J> = (J, JobMode);
+
+///The jobs a node sends to its children after it was updated, in the order they were executed.
+pub(crate) struct ChildJobs<J>{
+    ///Jobs for all children, as long as no job was sent to some children only
+    shared: Vec<CarriedJob<J>>,
+    ///Everything which was propagated after the first job for some children only
+    per_child: Vec<(Propagation<J>, JobMode)>,
+}
+
+impl<J: Clone> ChildJobs<J>{
+    pub(crate) fn new() -> Self{
+        ChildJobs{
+            shared: Vec::new(),
+            per_child: Vec::new(),
+        }
+    }
+
+    ///Adds what the next job propagated, the children get it in `mode`.
+    pub(crate) fn push(&mut self, propagation: Propagation<J>, mode: JobMode){
+        //once the children get different jobs, the order can only be kept by storing everything
+        if !self.per_child.is_empty(){
+            self.per_child.push((propagation, mode));
+            return;
+        }
+
+        match propagation{
+            Propagation::Stop => {},
+            Propagation::Forward(job) => self.shared.push((job, mode)),
+            Propagation::Fan(jobs) => self.shared.extend(jobs.into_iter().map(|job| (job, mode))),
+            per_child => self.per_child.push((per_child, mode)),
+        }
+    }
+
+    ///Returns true if no child gets any job
+    #[cfg(feature = "parallel")]
+    pub(crate) fn is_empty(&self) -> bool{
+        self.shared.is_empty() && self.per_child.is_empty()
+    }
+
+    ///Returns the jobs `child` gets. Only copies them if some jobs are not meant for all children.
+    pub(crate) fn for_child<T, A>(&self, child: &Node<T, J, A>) -> Cow<'_, [CarriedJob<J>]>
+    where T: NodeContent + Clone, A: Attribute<J> + Clone{
+        if self.per_child.is_empty(){
+            return Cow::Borrowed(&self.shared);
+        }
+
+        let mut jobs = self.shared.clone();
+        for (propagation, mode) in self.per_child.iter(){
+            match propagation{
+                Propagation::Stop => {},
+                Propagation::Forward(job) => jobs.push((job.clone(), *mode)),
+                Propagation::Fan(fanned) => jobs.extend(fanned.iter().map(|job| (job.clone(), *mode))),
+                Propagation::PerChild(child_jobs) => {
+                    if let Some(job) = child_jobs.get(&child.name){
+                        jobs.push((job.clone(), *mode));
+                    }
+                },
+            }
+        }
+        Cow::Owned(jobs)
+    }
+}
+
 ///Each type which implements `NodeContent` can be stored in a `Node`.
 ///
 /// **Why can't I change * of this object?**
@@ -43,6 +204,9 @@ pub trait NodeContent {
 }
 
 
+///A node split into mutable access to everything but its children, and its children.
+pub(crate) type SplitNode<'a, T, J, A> = (iter::NodeMut<'a, T, J, A>, &'a mut BTreeMap<String, Node<T, J, A>>);
+
 ///Describes a node for a `Tree`. Each Node can have child nodes as well as ONE value.
 #[derive(Clone)]
 pub struct Node<T,J,A>
@@ -56,12 +220,19 @@ pub struct Node<T,J,A>
     value: T,
     ///Contains all children of this node sorted by name.
     children: BTreeMap<String, Node<T, J, A>>,
-    ///Contains a list of things this node has to do when updated next
-    jobs: Vec<J>,
+    ///Contains a list of things this node has to do when updated next, each with its mode
+    jobs: Vec<CarriedJob<J>>,
+    ///Jobs for the parent, which executes them after all of its children were updated
+    upward: Vec<J>,
+    ///Jobs which are executed later or repeatedly, see `schedule()`
+    schedules: Vec<schedule::ScheduledJob<J>>,
     ///Can contain any type of attributes. Any `Job` can be applied to an attributes field.
     attributes: A,
     ///Can be a controller for this node which gets updated everytime this node is updated
     controller: Option<Arc<Mutex<NodeController<T,J,A> + Send >>> ,
+    ///True if this node or any of its children (recursively) might have something to do on the next update.
+    /// Idle subtrees are skipped while updating.
+    dirty: bool,
 }
 
 
@@ -81,8 +252,11 @@ impl<T,J,A> Node<T,J,A>
             value: value,
             children: BTreeMap::new(),
             jobs: Vec::new(),
+            upward: Vec::new(),
+            schedules: Vec::new(),
             attributes: attribute,
             controller: None,
+            dirty: false,
         }
     }
 
@@ -91,93 +265,290 @@ impl<T,J,A> Node<T,J,A>
     pub fn add_with_name(&mut self, new: T, name: String, attribute: A){
 
         //Create the node from child
-        let new_child_node = Node{
-            name: name.clone(),
-            value: new,
-            children: BTreeMap::new(),
-            jobs: Vec::new(),
-            attributes: attribute,
-            controller: None,
-        };
+        let mut new_child_node = Node::new(new, attribute);
+        new_child_node.set_name(name.clone());
         //add the child to self
         self.children.insert(name, new_child_node);
 
     }
 
-    ///Copys the name, value, attributes and tick closure into a new node object. Children and jobs are
-    /// reseted.
-    pub fn copy(&self) -> Self{
-        Node{
-            name: self.name.clone(),
-            value: self.value.clone(),
-            children: BTreeMap::new(),
-            jobs: Vec::new(),
-            attributes: self.attributes.clone(),
-            controller: None,
+    ///Converts self and all children (recursively) into nodes of other content and attribute types.
+    /// `convert` is called for every node and returns the new value and attributes. Names and the
+    /// hierarchy stay the same, jobs, scheduled jobs and controllers are not carried over.
+    /// Returns the first error `convert` returned, if any.
+    pub fn try_map<T2, J2, A2, E, F>(&self, convert: &mut F) -> Result<Node<T2, J2, A2>, E>
+    where T2: NodeContent + Clone, J2: Clone, A2: Attribute<J2> + Clone, F: FnMut(&Self) -> Result<(T2, A2), E>{
+        let (value, attributes) = convert(self)?;
+
+        let mut children = BTreeMap::new();
+        for (child_name, child) in self.children.iter(){
+            children.insert(child_name.clone(), child.try_map(convert)?);
         }
+
+        let mut mapped = Node::new(value, attributes);
+        mapped.set_name(self.name.clone());
+        mapped.children = children;
+        Ok(mapped)
     }
 
-    ///Returns the an `Ok(&mut Node)` at `path` if there is one at this location, or `Err()` if not.
-    pub fn get_node(&mut self, path: &mut Vec<String>) -> Result<&mut Self, tree::NodeErrors> {
-        //get the name of the child we are searching for if we get an `None` from the pop(),
-        // it means we reached the last child, so we return the node with this name as &mut T
-        // otherwise we search in the node with the name optained from pop() for the node ref.
-
-        let child_name = match path.pop(){
-            None =>{
-                //well this should be the node we searched for, returning &mut self
-                return Ok(self)
-            },
-            Some(node_with_this_name) => node_with_this_name,
-        };
+    ///Same as `try_map()` but `convert` can't fail.
+    pub fn map<T2, J2, A2, F>(&self, convert: &mut F) -> Node<T2, J2, A2>
+    where T2: NodeContent + Clone, J2: Clone, A2: Attribute<J2> + Clone, F: FnMut(&Self) -> (T2, A2){
+        match self.try_map(&mut |node| Ok::<(T2, A2), Infallible>(convert(node))){
+            Ok(mapped) => mapped,
+            Err(never) => match never {},
+        }
+    }
 
-        //It seams like we did not reach the end of the path, so we try to optain the node with the
-        //name from the .pop().
-        let node_with_name = {
-            match self.children.get_mut(&child_name){
+    ///Computes a value for self from the values of its children, which are computed the same way
+    /// first. `fold` gets a node and the results of its children (ordered by name) and returns the
+    /// result for the node. Returns the result for self.
+    pub fn fold_up<R, F>(&self, fold: &mut F) -> R where F: FnMut(&Self, &[R]) -> R{
+        let child_results: Vec<R> = self.children.values()
+            .map(|child| child.fold_up(fold))
+            .collect();
+        fold(self, &child_results)
+    }
+
+    ///Same as `fold_up()`, but also stores the results of all children (recursively) in `results`,
+    /// keyed by the node names. The result of self is only returned.
+    pub(crate) fn fold_up_into<R, F>(&self, fold: &mut F, results: &mut BTreeMap<String, R>) -> R
+    where F: FnMut(&Self, &[R]) -> R{
+        let child_results: Vec<R> = self.children.values()
+            .map(|child| child.fold_up_into(fold, results))
+            .collect();
+        let own_result = fold(self, &child_results);
+
+        for (child_name, child_result) in self.children.keys().zip(child_results){
+            results.insert(child_name.clone(), child_result);
+        }
+        own_result
+    }
+
+    ///Copys the name, value, attributes and tick closure into a new node object. Children, jobs and
+    /// scheduled jobs are reseted.
+    pub fn copy(&self) -> Self{
+        let mut copy = Node::new(self.value.clone(), self.attributes.clone());
+        copy.set_name(self.name.clone());
+        copy
+    }
+
+    ///Returns the an `Ok(&mut Node)` at `path` if there is one at this location, or `Err()` if not.
+    /// The `path` is relative to `self`, so the empty path returns `self`.
+    /// Every node on the way is marked dirty, since the returned node could get jobs or a controller.
+    pub fn get_node(&mut self, path: &NodePath) -> Result<&mut Self, tree::NodeErrors> {
+        //walk down the path one child name at a time
+        let mut current = self;
+        current.dirty = true;
+        for child_name in path.segments().iter(){
+            current = match current.children.get_mut(child_name){
                 //nice we got a child with this name
                 Some(child) => child,
                 //there is no such child, returning an error
                 None => return Err(tree::NodeErrors::NoNodeFound(String::from("could not find node in children"))),
-            }
-        };
+            };
+            current.dirty = true;
+        }
+        Ok(current)
+    }
 
-        //returning whatever we got from this child with the modified path
-        node_with_name.get_node(path)
+    ///Same as `get_node()` but returns a shared reference.
+    pub fn get_node_ref(&self, path: &NodePath) -> Result<&Self, tree::NodeErrors> {
+        let mut current = self;
+        for child_name in path.segments().iter(){
+            current = match current.children.get(child_name){
+                Some(child) => child,
+                None => return Err(tree::NodeErrors::NoNodeFound(String::from("could not find node in children"))),
+            };
+        }
+        Ok(current)
     }
 
 
     /// Applys `parent_jobs` first, then applies the jobs of `self.jobs`,
-    /// finally sends both to all children. `delta` is the time in seconds since the last update.
+    /// finally sends what the attributes propagated to the children. `delta` is the time in seconds since the last update.
     ///It will also execute the update function of this nodes value.
-    pub fn update(&mut self, delta: f32, parent_jobs: &Vec<J>){
+    /// Once the children are updated, the jobs they pushed up are executed and the `post_update()` of the
+    /// controller runs, see `push_up()`.
+    /// If neither this node nor any child has something to do and there are no `parent_jobs`, the
+    /// whole subtree is skipped.
+    pub fn update(&mut self, delta: f32, parent_jobs: &[J]){
+        let parent_jobs: Vec<CarriedJob<J>> = parent_jobs.iter()
+            .map(|job| (job.clone(), JobMode::Propagate))
+            .collect();
+        let context = UpdateContext{
+            delta,
+            merge: None,
+        };
+        self.update_carried(&context, &parent_jobs);
+    }
+
+    ///Same as `update()`, but every parent job comes with the mode it reaches self in.
+    pub(crate) fn update_carried(&mut self, context: &UpdateContext<J>, parent_jobs: &[CarriedJob<J>]){
+        if !self.dirty && parent_jobs.is_empty(){
+            return;
+        }
+
+        let child_jobs = self.update_self(context, parent_jobs);
+
+        //now send them to the children
+        for (_, child) in self.children.iter_mut(){
+            let jobs = child_jobs.for_child(child);
+            child.update_carried(context, &jobs);
+        }
+
+        let incoming = self.collect_upward();
+        self.update_upward(context.delta, incoming);
+        self.refresh_dirty();
+    }
+
+    ///Returns true if this node or any of its children (recursively) might have something to do on the
+    /// next update, for instance because they got jobs or a controller.
+    pub fn needs_update(&self) -> bool{
+        self.dirty
+    }
+
+    ///Makes sure this node is updated on the next update, even if it has no jobs or controller.
+    /// When changing a child directly, every node between the root and the child has to be marked.
+    /// Getting the child through `get_node()` or `get_children_mut()` already does that.
+    pub fn mark_dirty(&mut self){
+        self.dirty = true;
+    }
+
+    ///Takes the jobs all children pushed up, ordered by the names of the children.
+    fn collect_upward(&mut self) -> Vec<J>{
+        let mut incoming = Vec::new();
+        for (_, child) in self.children.iter_mut(){
+            incoming.append(&mut child.upward);
+        }
+        incoming
+    }
+
+    ///The second, bottom-up phase of updating this node, after its children were updated: executes the
+    /// jobs the children pushed up (`incoming`), then runs the `post_update()` of the controller.
+    pub(crate) fn update_upward(&mut self, delta: f32, incoming: Vec<J>){
+        for job in incoming.iter(){
+            if let Some(further) = self.attributes.execute_upward(job, delta){
+                self.upward.push(further);
+            }
+        }
 
         if let Some(control) = self.controller.clone(){
             let mut cont = control.lock().expect("failed");
-            cont.update(self);
+            cont.post_update(self, delta);
+        }
+    }
+
+    ///Pushes a `job` up to the parent of this node. The parent executes it with `Attribute::execute_upward()`
+    /// after all of its children were updated. Usually called by a `NodeController` in `post_update()`.
+    pub fn push_up(&mut self, job: J){
+        self.upward.push(job);
+        self.dirty = true;
+    }
+
+    ///Returns and clears the jobs this node pushed up which its parent hasn't taken yet. For the node
+    /// `update()` was called on, these are the jobs which would have gone above it.
+    pub fn take_upward(&mut self) -> Vec<J>{
+        mem::take(&mut self.upward)
+    }
+
+    ///After an update only nodes with a controller, new jobs or scheduled jobs, and their parents, stay dirty.
+    fn refresh_dirty(&mut self){
+        self.dirty = self.controller.is_some()
+            || !self.jobs.is_empty()
+            || !self.schedules.is_empty()
+            || self.children.values().any(|child| child.dirty);
+    }
+
+    ///Updates only this node: runs the controller, then applies `parent_jobs` and the jobs of `self.jobs`.
+    /// Returns the jobs which have to be sent to the children, the children themselfs are not touched.
+    pub(crate) fn update_self(&mut self, context: &UpdateContext<J>, parent_jobs: &[CarriedJob<J>]) -> ChildJobs<J>{
+        let delta = context.delta;
+
+        if let Some(control) = self.controller.clone(){
+            let mut cont = control.lock().expect("failed");
+            cont.update_with_delta(self, delta);
         }
 
 
         //first construct the final job vector to apply.
         //we clone the job because we don't want to apply jobs of one children to all children.
         // the append(self.jobs) will also empty self.jobs. This leaves room for adding new ones.
-        let mut job_vec = parent_jobs.clone();
+        let mut job_vec = parent_jobs.to_vec();
         job_vec.append(&mut self.jobs);
+        //scheduled jobs whose time has come follow, finished ones are dropped
+        let mut due = Vec::new();
+        self.schedules.retain_mut(|scheduled| !scheduled.tick(delta, &mut due));
+        job_vec.extend(due.into_iter().map(|job| (job, JobMode::Propagate)));
 
-        //now apply it ordered
-        for job in job_vec.iter_mut(){
-            *job = self.attributes.execute(&job);
+        if let Some(merge) = context.merge{
+            job_vec = merge_jobs(job_vec, merge);
         }
 
-        //now send them to the children
-        for (_, child) in self.children.iter_mut(){
-            child.update(delta, &job_vec);
+        //now apply it ordered, every job decides what reaches the children
+        let mut child_jobs = ChildJobs::new();
+        for (job, mode) in job_vec.into_iter(){
+            let propagation = if mode.executes_here(){
+                self.attributes.propagate(&job, delta)
+            }else{
+                //passed on unchanged
+                Propagation::Forward(job)
+            };
+
+            if let Some(child_mode) = mode.for_children(){
+                child_jobs.push(propagation, child_mode);
+            }
         }
+
+        child_jobs
     }
 
     ///Adds a job to this node
     pub fn add_job(&mut self, job: J){
-        self.jobs.push(job);
+        self.add_job_with_mode(job, JobMode::Propagate);
+    }
+
+    ///Adds a job to this node, `mode` decides whether it is executed on this node and how far it is
+    /// sent down to the children.
+    pub fn add_job_with_mode(&mut self, job: J, mode: JobMode){
+        self.jobs.push((job, mode));
+        self.dirty = true;
+    }
+
+    ///Schedules `job` to be executed as described by `schedule`. A due job is executed after the jobs
+    /// added with `add_job()` and sent to the children like them. Returns the id of the scheduled job,
+    /// which can be used to cancel it.
+    pub fn schedule(&mut self, job: J, schedule: schedule::Schedule) -> Result<usize, tree::NodeErrors>{
+        schedule.check()?;
+        Ok(self.add_scheduled(schedule::ScheduledJob::new(job, schedule)))
+    }
+
+    ///Spreads `job` over `duration` seconds: every update executes the part of the job which belongs
+    /// to the delta time of the update. Returns the id of the scheduled job.
+    pub fn schedule_over(&mut self, job: J, duration: f32) -> Result<usize, tree::NodeErrors>
+    where J: schedule::JobFraction{
+        schedule::check_positive(duration, "duration")?;
+        Ok(self.add_scheduled(schedule::ScheduledJob::over(job, duration, J::fraction)))
+    }
+
+    fn add_scheduled(&mut self, scheduled: schedule::ScheduledJob<J>) -> usize{
+        let id = scheduled.id();
+        self.schedules.push(scheduled);
+        self.dirty = true;
+        id
+    }
+
+    ///Cancels the scheduled job with this `id`. Returns false if there is no such job, for instance
+    /// because it was already executed.
+    pub fn cancel_scheduled(&mut self, id: usize) -> bool{
+        let count = self.schedules.len();
+        self.schedules.retain(|scheduled| scheduled.id() != id);
+        self.schedules.len() != count
+    }
+
+    ///Returns true if this node has jobs which are scheduled for later
+    pub fn has_scheduled(&self) -> bool{
+        !self.schedules.is_empty()
     }
 
     ///Immidiatly executes this job onto the node, usually used when setting up a node with default settings.
@@ -194,15 +565,16 @@ impl<T,J,A> Node<T,J,A>
     -> Result<(), tree::NodeErrors>{
         //first of all we need to add self to the new parent tree. This is necessary to keep the
         // current hierachy.
-        let new_name =
+        let new_id =
         match parent_tree.add(
             self.value.clone(),
             parent_node,
             Some(self.attributes.clone())
         ){
-            Ok(new_n) => new_n,
+            Ok(new_id) => new_id,
             Err(er) => return Err(er),
         };
+        let new_name = parent_tree.name_of_added(new_id)?;
 
 
 
@@ -224,6 +596,12 @@ impl<T,J,A> Node<T,J,A>
         self.name.clone()
     }
 
+    ///Changes the name of this node. The name is only changed on the node itself, the tree has to
+    /// keep its registry and the parents children up to date.
+    pub(crate) fn set_name(&mut self, new_name: String){
+        self.name = new_name;
+    }
+
     ///Returns a reference to the value of this node
     pub fn get_value(&self) -> &T{
         &self.value
@@ -234,9 +612,9 @@ impl<T,J,A> Node<T,J,A>
         &mut self.value
     }
 
-    ///Returns the current job vector of this node
-    pub fn get_jobs(&self) -> &Vec<J>{
-        &self.jobs
+    ///Returns the jobs which are queued on this node, in the order they are executed
+    pub fn get_jobs(&self) -> impl Iterator<Item = &J> + '_{
+        self.jobs.iter().map(|carried| &carried.0)
     }
 
     ///Returns a reference to the current atrributes.
@@ -257,7 +635,9 @@ impl<T,J,A> Node<T,J,A>
     }
 
     ///Returns the children as well, but mutable. Be careful what you do!
+    /// Marks self dirty, since the children could get jobs or controllers through it.
     pub fn get_children_mut(&mut self) -> &mut BTreeMap<String, Node<T,J,A>>{
+        self.dirty = true;
         &mut self.children
     }
 
@@ -269,6 +649,135 @@ impl<T,J,A> Node<T,J,A>
     ///Sets the inner controller to `Some(new)` controller
     pub fn set_controller<C>(&mut self, new: C) where C: NodeController<T,J,A> + Send + 'static{
         self.controller = Some(Arc::new(Mutex::new(new)));
+        self.dirty = true;
+    }
+
+    ///Returns an iterator over self and all children (recursively), depth first. Every node is
+    /// returned before its children. The paths are relative to self.
+    pub fn iter(&self) -> iter::Iter<'_, T, J, A>{
+        iter::Iter::new(self)
+    }
+
+    ///Same as `iter()`, but gives mutable access to everything except the children of each node.
+    pub fn iter_mut(&mut self) -> iter::IterMut<'_, T, J, A>{
+        iter::IterMut::new(self)
+    }
+
+    ///Returns an iterator over self and all children (recursively), breadth first.
+    pub fn iter_bfs(&self) -> iter::BfsIter<'_, T, J, A>{
+        iter::BfsIter::new(self)
+    }
+
+    ///Returns an iterator over self and all children (recursively), depth first. Every node is
+    /// returned after its children.
+    pub fn iter_post_order(&self) -> iter::PostOrderIter<'_, T, J, A>{
+        iter::PostOrderIter::new(self)
+    }
+
+    ///Drives the `visitor` over self and all children (recursively). Self is visited at depth 0.
+    /// Returns `VisitControl::Stop` if the visitor stopped the walk, `VisitControl::Continue` otherwise.
+    pub fn visit<V>(&mut self, visitor: &mut V) -> visit::VisitControl where V: visit::TreeVisitor<T, J, A>{
+        self.visit_at(visitor, 0)
+    }
+
+    fn visit_at<V>(&mut self, visitor: &mut V, depth: usize) -> visit::VisitControl where V: visit::TreeVisitor<T, J, A>{
+        match visitor.enter(self, depth){
+            visit::VisitControl::Stop => return visit::VisitControl::Stop,
+            visit::VisitControl::SkipChildren => {},
+            visit::VisitControl::Continue => {
+                for (_, child) in self.children.iter_mut(){
+                    let control = child.visit_at(visitor, depth + 1);
+                    //the visitor might have added jobs to the child
+                    self.dirty |= child.dirty;
+                    if control == visit::VisitControl::Stop{
+                        return visit::VisitControl::Stop;
+                    }
+                }
+            }
+        }
+
+        visitor.exit(self, depth);
+        visit::VisitControl::Continue
+    }
+
+    ///Splits self into mutable access to everything but the children, and the children.
+    /// Marks self dirty, since jobs could be added through both.
+    pub(crate) fn split_mut(&mut self) -> SplitNode<'_, T, J, A>{
+        self.dirty = true;
+        (
+            iter::NodeMut{
+                name: &self.name,
+                value: &mut self.value,
+                jobs: &mut self.jobs,
+                attributes: &mut self.attributes,
+            },
+            &mut self.children
+        )
+    }
+
+    ///Pushes self and all children (recursively) whose attributes match the `comparer` to `found`.
+    pub fn find_by_attributes<'a>(&'a self, comparer: &A::Comparer, found: &mut Vec<&'a Self>){
+        if self.attributes.compare(comparer){
+            found.push(self);
+        }
+        for (_, child) in self.children.iter(){
+            child.find_by_attributes(comparer, found);
+        }
+    }
+
+    ///Returns self or the first child (recursively) whose attributes match the `comparer`.
+    pub fn find_first_by_attributes(&self, comparer: &A::Comparer) -> Option<&Self>{
+        if self.attributes.compare(comparer){
+            return Some(self);
+        }
+        for (_, child) in self.children.iter(){
+            if let Some(found) = child.find_first_by_attributes(comparer){
+                return Some(found);
+            }
+        }
+        None
+    }
+
+    ///Calls `visitor` with self and every child (recursively) whose attributes match the `comparer`.
+    /// Returns how many nodes were visited.
+    pub fn for_each_by_attributes_mut<F>(&mut self, comparer: &A::Comparer, visitor: &mut F) -> usize
+    where F: FnMut(&mut Self){
+        let mut visited = 0;
+        if self.attributes.compare(comparer){
+            visitor(self);
+            visited += 1;
+        }
+        for (_, child) in self.children.iter_mut(){
+            visited += child.for_each_by_attributes_mut(comparer, visitor);
+            self.dirty |= child.dirty;
+        }
+        visited
+    }
+
+    ///Removes every child (recursively) for which `predicate` returns false. A removed child is
+    /// removed together with all of its children, without evaluating them. The names of all removed
+    /// nodes are pushed to `removed`.
+    pub(crate) fn retain_children<F>(&mut self, predicate: &mut F, removed: &mut Vec<String>)
+    where F: FnMut(&Self) -> bool{
+        self.children.retain(|_, child| {
+            let keep = predicate(child);
+            if !keep{
+                child.collect_names(removed);
+            }
+            keep
+        });
+
+        for (_, child) in self.children.iter_mut(){
+            child.retain_children(predicate, removed);
+        }
+    }
+
+    ///Pushes the name of self and the names of all children (recursively) to `names`.
+    pub(crate) fn collect_names(&self, names: &mut Vec<String>){
+        names.push(self.name.clone());
+        for (_, child) in self.children.iter(){
+            child.collect_names(names);
+        }
     }
 
     ///Prints self and then all children a level down and so on, creates a nice tree print out
@@ -293,3 +802,102 @@ impl<T,J,A> Node<T,J,A>
         }
     }
 }
+
+///Merges every job of `jobs` with the one before it if both have the same mode and `merge` can merge them.
+fn merge_jobs<J>(jobs: Vec<CarriedJob<J>>, merge: MergeFn<J>) -> Vec<CarriedJob<J>>{
+    let mut merged: Vec<CarriedJob<J>> = Vec::with_capacity(jobs.len());
+    for (job, mode) in jobs.into_iter(){
+        if let Some(last) = merged.last_mut(){
+            if last.1 == mode{
+                if let Some(combined) = merge(&last.0, &job){
+                    last.0 = combined;
+                    continue;
+                }
+            }
+        }
+        merged.push((job, mode));
+    }
+    merged
+}
+
+///Updating on several threads needs everything a node contains to be sendable
+#[cfg(feature = "parallel")]
+impl<T,J,A> Node<T,J,A>
+    where
+    T: NodeContent + Clone + Send,
+    J: Clone + Send + Sync,
+    A: Attribute<J> + Clone + Send,
+{
+    ///Same as `update()`, but the children are updated on up to `threads` threads. Every thread takes
+    /// the next child which hasn't been updated yet and updates its whole subtree, until no child
+    /// is left. If there is only one child, its children are spread over the threads instead.
+    /// Idle subtrees are skipped the same way `update()` skips them.
+    pub fn update_parallel(&mut self, delta: f32, parent_jobs: &[J], threads: usize){
+        let parent_jobs: Vec<CarriedJob<J>> = parent_jobs.iter()
+            .map(|job| (job.clone(), JobMode::Propagate))
+            .collect();
+        let context = UpdateContext{
+            delta,
+            merge: None,
+        };
+        self.update_parallel_carried(&context, &parent_jobs, threads);
+    }
+
+    ///Same as `update_parallel()`, but every parent job comes with the mode it reaches self in.
+    pub(crate) fn update_parallel_carried(&mut self, context: &UpdateContext<J>, parent_jobs: &[CarriedJob<J>], threads: usize){
+        if !self.dirty && parent_jobs.is_empty(){
+            return;
+        }
+
+        let child_jobs = self.update_self(context, parent_jobs);
+        self.update_children_parallel(context, &child_jobs, threads);
+
+        let incoming = self.collect_upward();
+        self.update_upward(context.delta, incoming);
+        self.refresh_dirty();
+    }
+
+    fn update_children_parallel(&mut self, context: &UpdateContext<J>, child_jobs: &ChildJobs<J>, threads: usize){
+        if threads <= 1{
+            for (_, child) in self.children.iter_mut(){
+                let jobs = child_jobs.for_child(child);
+                child.update_carried(context, &jobs);
+            }
+            return;
+        }
+
+        //only the children which have something to do are handed to the threads
+        let mut children: Vec<&mut Node<T, J, A>> = self.children.values_mut()
+            .filter(|child| child.dirty || !child_jobs.is_empty())
+            .collect();
+
+        if children.len() == 1{
+            for child in children.into_iter(){
+                let jobs = child_jobs.for_child(child);
+                child.update_parallel_carried(context, &jobs, threads);
+            }
+            return;
+        }
+
+        let worker_count = threads.min(children.len());
+        let next_children = Mutex::new(children.iter_mut());
+        let next_children = &next_children;
+        thread::scope(|scope| {
+            for _ in 0..worker_count{
+                scope.spawn(move || {
+                    loop {
+                        //only hold the lock while taking the next child
+                        let next_child = next_children.lock().expect("failed to lock children").next();
+                        match next_child{
+                            Some(child) => {
+                                let jobs = child_jobs.for_child(child);
+                                child.update_carried(context, &jobs);
+                            },
+                            None => break,
+                        }
+                    }
+                });
+            }
+        });
+    }
+}
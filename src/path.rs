@@ -0,0 +1,232 @@
+use std::fmt;
+use std::str::FromStr;
+
+use tree::NodeErrors;
+
+///The path to a node of a tree: the names of all nodes on the way from the root node (excluded)
+/// down to the node itself. The root node has the empty path.
+///
+/// A path is written like `/Cave/LeftSide/Teddy` where the root node is `/`. A `/` or `\` within a
+/// node name is escaped with a `\`, so every node name survives `to_string()` and `parse()` unchanged.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Default)]
+pub struct NodePath{
+    segments: Vec<String>,
+}
+
+impl NodePath{
+    ///Returns the path of the root node
+    pub fn root() -> Self{
+        NodePath{
+            segments: Vec::new(),
+        }
+    }
+
+    ///Creates a path from the names of the nodes on the way from the root node down.
+    pub fn from_segments<I, S>(segments: I) -> Self where I: IntoIterator<Item = S>, S: Into<String>{
+        NodePath{
+            segments: segments.into_iter().map(|segment| segment.into()).collect(),
+        }
+    }
+
+    ///Parses a path in the form `/Cave/LeftSide/Teddy` or `/` for the root node.
+    /// Returns an `Err(e)` if the path doesn't start with `/`, contains an empty name or ends in an
+    /// unfinished escape sequence.
+    pub fn parse(text: &str) -> Result<Self, NodeErrors>{
+        let mut chars = text.chars();
+        if chars.next() != Some('/'){
+            return Err(NodeErrors::InvalidPath(String::from("A path has to start with '/': ") + text));
+        }
+
+        let mut segments = Vec::new();
+        let mut current = String::new();
+        let mut has_segment = false;
+        while let Some(character) = chars.next(){
+            match character{
+                '\\' => {
+                    match chars.next(){
+                        Some(escaped) => current.push(escaped),
+                        None => return Err(NodeErrors::InvalidPath(
+                            String::from("Unfinished escape sequence at the end of ") + text
+                        )),
+                    }
+                    has_segment = true;
+                },
+                '/' => {
+                    if current.is_empty(){
+                        return Err(NodeErrors::InvalidPath(String::from("Empty node name in ") + text));
+                    }
+                    segments.push(current);
+                    current = String::new();
+                    has_segment = false;
+                },
+                _ => {
+                    current.push(character);
+                    has_segment = true;
+                }
+            }
+        }
+
+        if has_segment{
+            segments.push(current);
+        }else if !segments.is_empty(){
+            //a path like "/Cave/"
+            return Err(NodeErrors::InvalidPath(String::from("Empty node name at the end of ") + text));
+        }
+
+        Ok(NodePath{
+            segments,
+        })
+    }
+
+    ///Returns the names of all nodes on this path, starting below the root node
+    pub fn segments(&self) -> &[String]{
+        &self.segments
+    }
+
+    ///Returns how many levels below the root node the path points, 0 for the root node itself
+    pub fn depth(&self) -> usize{
+        self.segments.len()
+    }
+
+    ///Returns true if this is the path of the root node
+    pub fn is_root(&self) -> bool{
+        self.segments.is_empty()
+    }
+
+    ///Returns the name of the last node on this path, or `None` for the root node
+    pub fn name(&self) -> Option<&str>{
+        self.segments.last().map(|segment| segment.as_str())
+    }
+
+    ///Returns the path of the child `name` of the node at this path
+    pub fn join(&self, name: &str) -> Self{
+        let mut joined = self.clone();
+        joined.push(name);
+        joined
+    }
+
+    ///Appends the child `name` to this path
+    pub fn push(&mut self, name: &str){
+        self.segments.push(name.to_string());
+    }
+
+    ///Returns `relative`, but starting at the node of this path instead of the root node
+    pub fn concat(&self, relative: &NodePath) -> Self{
+        let mut joined = self.clone();
+        joined.segments.extend(relative.segments.iter().cloned());
+        joined
+    }
+
+    ///Returns the path of the parent node, or `None` for the root node
+    pub fn parent(&self) -> Option<Self>{
+        if self.is_root(){
+            return None;
+        }
+        Some(NodePath{
+            segments: self.segments[..self.segments.len() - 1].to_vec(),
+        })
+    }
+
+    ///Returns true if `base` is this path or one of its parents
+    pub fn starts_with(&self, base: &NodePath) -> bool{
+        self.segments.starts_with(&base.segments)
+    }
+
+    ///Returns the rest of this path below `base`, or `None` if this path doesn't start with `base`.
+    pub fn strip_prefix(&self, base: &NodePath) -> Option<Self>{
+        if !self.starts_with(base){
+            return None;
+        }
+        Some(NodePath{
+            segments: self.segments[base.segments.len()..].to_vec(),
+        })
+    }
+}
+
+impl fmt::Display for NodePath{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result{
+        if self.is_root(){
+            return write!(f, "/");
+        }
+
+        for segment in self.segments.iter(){
+            write!(f, "/")?;
+            for character in segment.chars(){
+                if character == '/' || character == '\\'{
+                    write!(f, "\\")?;
+                }
+                write!(f, "{}", character)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for NodePath{
+    type Err = NodeErrors;
+
+    fn from_str(text: &str) -> Result<Self, NodeErrors>{
+        NodePath::parse(text)
+    }
+}
+
+#[cfg(test)]
+mod tests{
+    use super::NodePath;
+
+    #[test]
+    fn parse_root(){
+        assert_eq!(NodePath::parse("/").unwrap(), NodePath::root());
+    }
+
+    #[test]
+    fn parse_rejects_empty_names(){
+        assert!(NodePath::parse("").is_err());
+        assert!(NodePath::parse("//").is_err());
+        assert!(NodePath::parse("/a/").is_err());
+        assert!(NodePath::parse("/a//b").is_err());
+        assert!(NodePath::parse("a/b").is_err());
+    }
+
+    #[test]
+    fn parse_rejects_trailing_escape(){
+        assert!(NodePath::parse("/\\").is_err());
+        assert!(NodePath::parse("/a\\").is_err());
+    }
+
+    #[test]
+    fn parse_escaped_names(){
+        let path = NodePath::parse("/Cave\\/Left/Te\\\\ddy").unwrap();
+        assert_eq!(path.segments(), &["Cave/Left".to_string(), "Te\\ddy".to_string()]);
+        assert_eq!(path.to_string(), "/Cave\\/Left/Te\\\\ddy");
+    }
+
+    #[test]
+    fn names_survive_round_trip(){
+        let names = ["plain", "a/b", "a\\b", "/", "\\", "\\/", "//", "end\\", " space "];
+        for name in names.iter(){
+            let path = NodePath::from_segments(vec!["Root", name, "Leaf"]);
+            assert_eq!(NodePath::parse(&path.to_string()).unwrap(), path, "{}", name);
+        }
+        assert_eq!(NodePath::parse(&NodePath::root().to_string()).unwrap(), NodePath::root());
+    }
+
+    #[test]
+    fn prefix_of_equal_length(){
+        let path = NodePath::from_segments(vec!["a", "b"]);
+        let other = NodePath::from_segments(vec!["a", "c"]);
+
+        assert!(path.starts_with(&path));
+        assert_eq!(path.strip_prefix(&path), Some(NodePath::root()));
+        assert!(!path.starts_with(&other));
+        assert_eq!(path.strip_prefix(&other), None);
+    }
+
+    #[test]
+    fn prefix_of_longer_path(){
+        let path = NodePath::from_segments(vec!["a", "b"]);
+        assert_eq!(path.strip_prefix(&NodePath::from_segments(vec!["a"])), Some(NodePath::from_segments(vec!["b"])));
+        assert_eq!(path.strip_prefix(&NodePath::from_segments(vec!["a", "b", "c"])), None);
+        assert_eq!(path.strip_prefix(&NodePath::root()), Some(path.clone()));
+    }
+}
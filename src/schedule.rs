@@ -0,0 +1,304 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use id::NodeId;
+use tree::NodeErrors;
+
+///Source of the ids of scheduled jobs, every scheduled job gets its own one.
+static NEXT_SCHEDULE_ID: AtomicUsize = AtomicUsize::new(0);
+
+///How often a repeated job can be due in a single update at most. Time beyond that is dropped, like
+/// the fixed steps of a `TimeStep`, so a tiny interval can't flood the jobs of a node.
+const MAX_RUNS_PER_TICK: u32 = 8;
+
+///Describes when a scheduled job is executed. All times are in seconds of the delta time the nodes
+/// are updated with, so they follow the `TimeStep` of the tree.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Schedule{
+    ///Executes the job once, after the given time passed.
+    After(f32),
+    ///Executes the job every time the given interval passed, until it is cancelled. The job is due
+    /// 8 times per update at most, if the interval is shorter the rest of the time is dropped.
+    Every(f32),
+    ///Executes the job every time the `interval` passed, `count` times in total. Like `Every` the
+    /// job is due 8 times per update at most.
+    Repeat{
+        interval: f32,
+        count: u32,
+    },
+}
+
+impl Schedule{
+    ///Returns an error if the times of this schedule can't be used. Delays can't be negative, intervals
+    /// have to be positive and a job has to be repeated at least once.
+    pub(crate) fn check(&self) -> Result<(), NodeErrors>{
+        match *self{
+            Schedule::After(delay) => {
+                if !delay.is_finite() || delay < 0.0{
+                    return Err(NodeErrors::InvalidSchedule(format!("{} is no valid delay", delay)));
+                }
+            },
+            Schedule::Every(interval) => check_positive(interval, "interval")?,
+            Schedule::Repeat{interval, count} => {
+                check_positive(interval, "interval")?;
+                if count == 0{
+                    return Err(NodeErrors::InvalidSchedule(String::from("a job has to be repeated at least once")));
+                }
+            },
+        }
+        Ok(())
+    }
+}
+
+///Returns an error if `time` is not a positive, finite number of seconds. `what` names the time in the error.
+pub(crate) fn check_positive(time: f32, what: &str) -> Result<(), NodeErrors>{
+    if !time.is_finite() || time <= 0.0{
+        return Err(NodeErrors::InvalidSchedule(format!("{} is no valid {}", time, what)));
+    }
+    Ok(())
+}
+
+///Jobs which can be split into parts. Needed to spread a job over some time with `Tree::schedule_over()`.
+pub trait JobFraction{
+    ///Returns the part of self which belongs to `fraction` (between 0.0 and 1.0) of the whole job.
+    /// For instance a translation by 5 units should return a translation by 0.5 units for a fraction of 0.1.
+    fn fraction(&self, fraction: f32) -> Self;
+}
+
+///Points to a scheduled job of a node, can be used to cancel it with `Tree::cancel()`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct ScheduleHandle{
+    ///The node the job was scheduled on
+    node: NodeId,
+    ///The id of the job on that node
+    id: usize,
+}
+
+impl ScheduleHandle{
+    ///Creates a handle for the scheduled job `id` on `node`.
+    pub(crate) fn new(node: NodeId, id: usize) -> Self{
+        ScheduleHandle{
+            node,
+            id,
+        }
+    }
+
+    ///Returns the id of the node the job was scheduled on
+    pub fn node(&self) -> NodeId{
+        self.node
+    }
+
+    ///Returns the id of the job, as returned by `Node::schedule()`
+    pub fn id(&self) -> usize{
+        self.id
+    }
+}
+
+///How much time is left for a scheduled job.
+#[derive(Clone)]
+enum Timing<J>{
+    After{
+        remaining: f32,
+    },
+    Every{
+        interval: f32,
+        elapsed: f32,
+    },
+    Repeat{
+        interval: f32,
+        elapsed: f32,
+        count: u32,
+    },
+    Over{
+        duration: f32,
+        remaining: f32,
+        fraction: fn(&J, f32) -> J,
+    },
+}
+
+///A job which waits on a node until its time has come.
+#[derive(Clone)]
+pub(crate) struct ScheduledJob<J>{
+    id: usize,
+    job: J,
+    timing: Timing<J>,
+}
+
+impl<J: Clone> ScheduledJob<J>{
+    ///Creates a job which is executed as described by `schedule`.
+    pub(crate) fn new(job: J, schedule: Schedule) -> Self{
+        let timing = match schedule{
+            Schedule::After(delay) => Timing::After{
+                remaining: delay,
+            },
+            Schedule::Every(interval) => Timing::Every{
+                interval,
+                elapsed: 0.0,
+            },
+            Schedule::Repeat{interval, count} => Timing::Repeat{
+                interval,
+                elapsed: 0.0,
+                count,
+            },
+        };
+        ScheduledJob::with_timing(job, timing)
+    }
+
+    ///Creates a job which is spread over `duration`, the part of every update is created with `fraction`.
+    pub(crate) fn over(job: J, duration: f32, fraction: fn(&J, f32) -> J) -> Self{
+        ScheduledJob::with_timing(job, Timing::Over{
+            duration,
+            remaining: duration,
+            fraction,
+        })
+    }
+
+    fn with_timing(job: J, timing: Timing<J>) -> Self{
+        ScheduledJob{
+            id: NEXT_SCHEDULE_ID.fetch_add(1, Ordering::Relaxed),
+            job,
+            timing,
+        }
+    }
+
+    ///Returns the id of this job
+    pub(crate) fn id(&self) -> usize{
+        self.id
+    }
+
+    ///Lets `delta` seconds pass and pushes every job which is due to `due`. Returns true once this
+    /// job has nothing left to do.
+    pub(crate) fn tick(&mut self, delta: f32, due: &mut Vec<J>) -> bool{
+        match self.timing{
+            Timing::After{ref mut remaining} => {
+                *remaining -= delta;
+                if *remaining <= 0.0{
+                    due.push(self.job.clone());
+                    return true;
+                }
+                false
+            },
+            Timing::Every{interval, ref mut elapsed} => {
+                *elapsed += delta;
+                let runs = take_runs(elapsed, interval, MAX_RUNS_PER_TICK);
+                for _ in 0..runs{
+                    due.push(self.job.clone());
+                }
+                false
+            },
+            Timing::Repeat{interval, ref mut elapsed, ref mut count} => {
+                *elapsed += delta;
+                let runs = take_runs(elapsed, interval, MAX_RUNS_PER_TICK.min(*count));
+                for _ in 0..runs{
+                    due.push(self.job.clone());
+                }
+                *count -= runs;
+                *count == 0
+            },
+            Timing::Over{duration, ref mut remaining, fraction} => {
+                //the last part only covers the time which was left
+                let part = delta.min(*remaining);
+                *remaining -= part;
+                if part > 0.0{
+                    due.push(fraction(&self.job, part / duration));
+                }
+                *remaining <= 0.0
+            },
+        }
+    }
+}
+
+///Returns how often `interval` fits into `elapsed`, but `limit` times at most, and removes that time
+/// from `elapsed`. If the limit was hit, whole intervals which are left are dropped as well.
+fn take_runs(elapsed: &mut f32, interval: f32, limit: u32) -> u32{
+    let fitting = (*elapsed / interval).floor();
+    if fitting < 1.0{
+        return 0;
+    }
+    let runs = fitting.min(limit as f32);
+    *elapsed -= runs * interval;
+    //don't try to catch up with time we couldn't use
+    if fitting > runs{
+        *elapsed %= interval;
+    }
+    runs as u32
+}
+
+#[cfg(test)]
+mod tests{
+    use super::{MAX_RUNS_PER_TICK, Schedule, ScheduledJob};
+
+    ///Ticks `job` once with `delta`, returns how many jobs were due and if the job is finished.
+    fn tick(job: &mut ScheduledJob<f32>, delta: f32) -> (usize, bool){
+        let mut due = Vec::new();
+        let finished = job.tick(delta, &mut due);
+        (due.len(), finished)
+    }
+
+    fn part(job: &f32, fraction: f32) -> f32{
+        job * fraction
+    }
+
+    #[test]
+    fn after_zero_fires_on_next_tick(){
+        let mut job = ScheduledJob::new(1.0, Schedule::After(0.0));
+        assert_eq!(tick(&mut job, 0.0), (1, true));
+
+        let mut job = ScheduledJob::new(1.0, Schedule::After(0.0));
+        assert_eq!(tick(&mut job, 0.25), (1, true));
+    }
+
+    #[test]
+    fn after_waits_for_delay(){
+        let mut job = ScheduledJob::new(1.0, Schedule::After(0.5));
+        assert_eq!(tick(&mut job, 0.25), (0, false));
+        assert_eq!(tick(&mut job, 0.25), (1, true));
+    }
+
+    #[test]
+    fn every_is_capped_per_tick(){
+        let mut job = ScheduledJob::new(1.0, Schedule::Every(0.25));
+        assert_eq!(tick(&mut job, 100.0), (MAX_RUNS_PER_TICK as usize, false));
+        //the time which didn't fit was dropped
+        assert_eq!(tick(&mut job, 0.125), (0, false));
+        assert_eq!(tick(&mut job, 0.125), (1, false));
+        assert_eq!(tick(&mut job, 0.5), (2, false));
+    }
+
+    #[test]
+    fn tiny_interval_is_capped(){
+        let mut job = ScheduledJob::new(1.0, Schedule::Every(1e-9));
+        assert_eq!(tick(&mut job, 1.0), (MAX_RUNS_PER_TICK as usize, false));
+    }
+
+    #[test]
+    fn repeat_finishes_after_count(){
+        let mut job = ScheduledJob::new(1.0, Schedule::Repeat{interval: 0.25, count: 3});
+        assert_eq!(tick(&mut job, 0.5), (2, false));
+        assert_eq!(tick(&mut job, 0.5), (1, true));
+
+        let mut job = ScheduledJob::new(1.0, Schedule::Repeat{interval: 0.25, count: 10});
+        assert_eq!(tick(&mut job, 100.0), (MAX_RUNS_PER_TICK as usize, false));
+        assert_eq!(tick(&mut job, 100.0), (2, true));
+    }
+
+    #[test]
+    fn over_fractions_sum_to_one(){
+        let mut job = ScheduledJob::over(1.0, 1.0, part);
+        let mut due = Vec::new();
+        assert!(!job.tick(0.375, &mut due));
+        assert!(!job.tick(0.375, &mut due));
+        //overshoots the duration, only the remaining 0.25 are used
+        assert!(job.tick(0.5, &mut due));
+        assert_eq!(due, vec![0.375, 0.375, 0.25]);
+        assert_eq!(due.iter().sum::<f32>(), 1.0);
+    }
+
+    #[test]
+    fn rejects_invalid_schedules(){
+        assert!(Schedule::After(-1.0).check().is_err());
+        assert!(Schedule::After(0.0).check().is_ok());
+        assert!(Schedule::Every(0.0).check().is_err());
+        assert!(Schedule::Every(f32::NAN).check().is_err());
+        assert!(Schedule::Repeat{interval: 0.25, count: 0}.check().is_err());
+    }
+}
@@ -0,0 +1,372 @@
+use node;
+use tree::NodeErrors;
+
+///A single token of a name pattern
+#[derive(Clone, Debug, PartialEq)]
+enum Token{
+    ///Matches exactly this character
+    Literal(char),
+    ///`*`, matches any number of characters
+    AnyChars,
+    ///`?`, matches exactly one character
+    AnyChar,
+}
+
+///One level of a selector
+#[derive(Clone, Debug, PartialEq)]
+enum Segment{
+    ///`**`, matches any number of levels, including none
+    AnyDepth,
+    ///Matches a single node whose name matches the pattern
+    Name(Vec<Token>),
+}
+
+///A selector addresses a group of nodes by the names on their way from the root node down.
+///
+/// The selector is written like a path where every level can contain wildcards:
+///
+/// - `*` matches any number of characters within a name, `?` exactly one character
+///
+/// - `**` as a whole level matches any number of levels, including none
+///
+/// - `\` escapes the next character, for instance `\*` or `\/`
+///
+/// The first level is matched against the name of the root node. `RootNode/*/Teddy` selects every
+/// node called `Teddy` two levels below the root node, `**/Light*` every node whose name starts with
+/// `Light`, no matter where it is.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Selector{
+    segments: Vec<Segment>,
+}
+
+impl Selector{
+    ///Parses a `pattern`. Returns an `Err(e)` if the pattern contains an empty level or ends in an
+    /// unfinished escape sequence.
+    pub fn parse(pattern: &str) -> Result<Self, NodeErrors>{
+        let mut segments = Vec::new();
+        let mut tokens = Vec::new();
+        //true if the current level is exactly `**`, escaped stars don't count
+        let mut only_stars = true;
+
+        let mut chars = pattern.chars();
+        loop {
+            let character = chars.next();
+            match character{
+                Some('\\') => {
+                    match chars.next(){
+                        Some(escaped) => tokens.push(Token::Literal(escaped)),
+                        None => return Err(NodeErrors::InvalidSelector(
+                            String::from("Unfinished escape sequence at the end of ") + pattern
+                        )),
+                    }
+                    only_stars = false;
+                },
+                Some('*') => tokens.push(Token::AnyChars),
+                Some('?') => {
+                    tokens.push(Token::AnyChar);
+                    only_stars = false;
+                },
+                Some('/') | None => {
+                    if tokens.is_empty(){
+                        return Err(NodeErrors::InvalidSelector(String::from("Empty level in ") + pattern));
+                    }
+                    if only_stars && tokens.len() == 2{
+                        segments.push(Segment::AnyDepth);
+                    }else{
+                        segments.push(Segment::Name(tokens));
+                    }
+                    tokens = Vec::new();
+                    only_stars = true;
+
+                    if character.is_none(){
+                        break;
+                    }
+                },
+                Some(other) => {
+                    tokens.push(Token::Literal(other));
+                    only_stars = false;
+                }
+            }
+        }
+
+        Ok(Selector{
+            segments,
+        })
+    }
+
+    ///Returns true if the selector matches a node with these `names` on its way from the
+    /// root node (first) down to the node itself (last).
+    pub fn matches(&self, names: &[String]) -> bool{
+        let mut states = self.start();
+        for name in names.iter(){
+            states = self.advance(&states, name);
+            if states.is_empty(){
+                return false;
+            }
+        }
+        self.is_match(&states)
+    }
+
+    ///Returns the states before the root node was matched. A state is the index of the next level
+    /// which has to be matched.
+    pub(crate) fn start(&self) -> Vec<usize>{
+        self.closure(vec![0])
+    }
+
+    ///Returns the states after a node called `name` was matched in one of the `states`.
+    pub(crate) fn advance(&self, states: &[usize], name: &str) -> Vec<usize>{
+        let mut next = Vec::new();
+        for state in states.iter(){
+            match self.segments.get(*state){
+                Some(Segment::AnyDepth) => next.push(*state),
+                Some(Segment::Name(tokens)) if name_matches(tokens, &name.chars().collect::<Vec<_>>()) => {
+                    next.push(*state + 1);
+                },
+                _ => {},
+            }
+        }
+        self.closure(next)
+    }
+
+    ///Returns true if the node which lead to `states` is selected
+    pub(crate) fn is_match(&self, states: &[usize]) -> bool{
+        states.contains(&self.segments.len())
+    }
+
+    ///Adds the states which are reached by skipping `**` levels
+    fn closure(&self, mut states: Vec<usize>) -> Vec<usize>{
+        let mut index = 0;
+        while index < states.len(){
+            let state = states[index];
+            if let Some(Segment::AnyDepth) = self.segments.get(state){
+                if !states.contains(&(state + 1)){
+                    states.push(state + 1);
+                }
+            }
+            index += 1;
+        }
+        states.sort();
+        states.dedup();
+        states
+    }
+}
+
+///Returns true if the `name` matches the `tokens` of a single level
+fn name_matches(tokens: &[Token], name: &[char]) -> bool{
+    let mut token_index = 0;
+    let mut name_index = 0;
+    //the token after the last star, and the position in the name the star matches up to
+    let mut last_star: Option<(usize, usize)> = None;
+
+    while name_index < name.len(){
+        match tokens.get(token_index){
+            Some(Token::AnyChars) => {
+                last_star = Some((token_index + 1, name_index));
+                token_index += 1;
+                continue;
+            },
+            Some(Token::AnyChar) => {
+                token_index += 1;
+                name_index += 1;
+                continue;
+            },
+            Some(Token::Literal(character)) if *character == name[name_index] => {
+                token_index += 1;
+                name_index += 1;
+                continue;
+            },
+            _ => {},
+        }
+
+        //no match, let the last star take one more character and try again from there.
+        // Earlier stars never have to be retried, the last one can cover everything they could.
+        match last_star{
+            Some((after_star, star_end)) => {
+                token_index = after_star;
+                name_index = star_end + 1;
+                last_star = Some((after_star, star_end + 1));
+            },
+            None => return false,
+        }
+    }
+
+    //only stars can match the empty rest of the name
+    tokens[token_index..].iter().all(|token| *token == Token::AnyChars)
+}
+
+///Pushes the names of `node` and all of its children which are selected to `selected`.
+/// `parent_states` are the selector states of the parent of `node`.
+pub(crate) fn collect_selected<T, J, A>(
+    node: &node::Node<T, J, A>,
+    selector: &Selector,
+    parent_states: &[usize],
+    selected: &mut Vec<String>
+) where T: node::NodeContent + Clone, J: Clone, A: node::Attribute<J> + Clone{
+    let states = selector.advance(parent_states, &node.get_name());
+    //nothing below this node can match anymore
+    if states.is_empty(){
+        return;
+    }
+    if selector.is_match(&states){
+        selected.push(node.get_name());
+    }
+    for (_, child) in node.get_children().iter(){
+        collect_selected(child, selector, &states, selected);
+    }
+}
+
+///Calls `visitor` for `node` and all of its children which are selected. Returns how many nodes
+/// were visited.
+pub(crate) fn visit_selected<T, J, A, F>(
+    node: &mut node::Node<T, J, A>,
+    selector: &Selector,
+    parent_states: &[usize],
+    visitor: &mut F
+) -> usize where T: node::NodeContent + Clone, J: Clone, A: node::Attribute<J> + Clone, F: FnMut(&mut node::Node<T, J, A>){
+    let states = selector.advance(parent_states, &node.get_name());
+    if states.is_empty(){
+        return 0;
+    }
+    let mut visited = 0;
+    if selector.is_match(&states){
+        visitor(node);
+        visited += 1;
+    }
+    for (_, child) in node.get_children_mut().iter_mut(){
+        visited += visit_selected(child, selector, &states, visitor);
+    }
+    visited
+}
+
+#[cfg(test)]
+mod tests{
+    use super::Selector;
+    use node;
+    use tree;
+
+    fn names(names: &[&str]) -> Vec<String>{
+        names.iter().map(|name| name.to_string()).collect()
+    }
+
+    fn matches(pattern: &str, path: &[&str]) -> bool{
+        Selector::parse(pattern).unwrap().matches(&names(path))
+    }
+
+    #[test]
+    fn any_depth_at_start(){
+        assert!(matches("**/Teddy", &["Teddy"]));
+        assert!(matches("**/Teddy", &["RootNode", "Teddy"]));
+        assert!(matches("**/Teddy", &["RootNode", "Cave", "Left", "Teddy"]));
+        assert!(!matches("**/Teddy", &["RootNode", "Teddy", "Arm"]));
+    }
+
+    #[test]
+    fn any_depth_in_the_middle(){
+        assert!(matches("RootNode/**/Teddy", &["RootNode", "Teddy"]));
+        assert!(matches("RootNode/**/Teddy", &["RootNode", "Cave", "Left", "Teddy"]));
+        assert!(!matches("RootNode/**/Teddy", &["Other", "Cave", "Teddy"]));
+        assert!(!matches("RootNode/**/Teddy", &["RootNode"]));
+    }
+
+    #[test]
+    fn any_depth_at_end(){
+        assert!(matches("RootNode/**", &["RootNode"]));
+        assert!(matches("RootNode/**", &["RootNode", "Cave", "Teddy"]));
+        assert!(!matches("RootNode/**", &["Other", "Cave"]));
+    }
+
+    #[test]
+    fn escaped_star_is_literal(){
+        assert!(matches("RootNode/\\*", &["RootNode", "*"]));
+        assert!(!matches("RootNode/\\*", &["RootNode", "Teddy"]));
+        assert!(matches("RootNode/a\\*b", &["RootNode", "a*b"]));
+        assert!(!matches("RootNode/a\\*b", &["RootNode", "axb"]));
+        //an escaped star is no `**` level either
+        assert!(!matches("RootNode/\\**", &["RootNode", "Cave", "Teddy"]));
+    }
+
+    #[test]
+    fn three_stars_match_a_single_level(){
+        assert!(matches("RootNode/***", &["RootNode", "Cave"]));
+        assert!(!matches("RootNode/***", &["RootNode"]));
+        assert!(!matches("RootNode/***", &["RootNode", "Cave", "Teddy"]));
+    }
+
+    #[test]
+    fn wildcards_within_a_name(){
+        assert!(matches("Light*", &["Light"]));
+        assert!(matches("Light*", &["LightOne"]));
+        assert!(matches("*Light*One", &["TheLightOfOne"]));
+        assert!(!matches("*Light*One", &["TheLightOfOnes"]));
+        assert!(matches("T?ddy", &["Teddy"]));
+        assert!(!matches("T?ddy", &["Tddy"]));
+    }
+
+    #[test]
+    fn many_stars_on_long_names(){
+        let name = "a".repeat(200);
+        assert!(!matches("*a*a*a*a*a*a*a*a*a*a*a*a*a*a*a*a*b", &[name.as_str()]));
+        assert!(matches("*a*a*a*a*a*a*a*a*a*a*a*a*a*a*a*a*", &[name.as_str()]));
+    }
+
+    #[test]
+    fn rejects_invalid_patterns(){
+        assert!(Selector::parse("").is_err());
+        assert!(Selector::parse("/RootNode").is_err());
+        assert!(Selector::parse("RootNode//Teddy").is_err());
+        assert!(Selector::parse("RootNode/").is_err());
+        assert!(Selector::parse("RootNode/Teddy\\").is_err());
+    }
+
+    #[derive(Clone)]
+    struct Named(String);
+
+    impl node::NodeContent for Named{
+        fn get_name(&self) -> String{
+            self.0.clone()
+        }
+    }
+
+    #[derive(Clone)]
+    struct NoAttributes;
+
+    impl node::Attribute<()> for NoAttributes{
+        type Comparer = ();
+
+        fn default() -> Self{
+            NoAttributes
+        }
+
+        fn execute(&mut self, _job: &()){}
+
+        fn print_atr(&self, _lvl: i32){}
+
+        fn compare(&self, _attributes: &()) -> bool{
+            true
+        }
+    }
+
+    fn small_tree() -> tree::Tree<Named, (), NoAttributes>{
+        let mut tree = tree::Tree::new(Named("RootNode".to_string()), NoAttributes);
+        tree.add_at_root(Named("Cave".to_string()), None).unwrap();
+        tree.add(Named("Teddy".to_string()), "Cave".to_string(), None).unwrap();
+        tree.add(Named("LightA".to_string()), "Cave".to_string(), None).unwrap();
+        tree.add_at_root(Named("Forest".to_string()), None).unwrap();
+        tree.add(Named("Deep".to_string()), "Forest".to_string(), None).unwrap();
+        tree.add(Named("LightB".to_string()), "Deep".to_string(), None).unwrap();
+        tree.add_at_root(Named("LightC".to_string()), None).unwrap();
+        tree
+    }
+
+    #[test]
+    fn select_in_tree(){
+        let tree = small_tree();
+
+        assert_eq!(tree.select("RootNode/*/Teddy").unwrap(), names(&["Teddy"]));
+        let mut lights = tree.select("**/Light*").unwrap();
+        lights.sort();
+        assert_eq!(lights, names(&["LightA", "LightB", "LightC"]));
+        assert_eq!(tree.select("RootNode/*/Light*").unwrap(), names(&["LightA"]));
+        assert!(tree.select("Other/**").unwrap().is_empty());
+    }
+}
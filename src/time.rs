@@ -0,0 +1,323 @@
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+
+use tree::NodeErrors;
+
+///A source of time for a tree. `Tree::update()` asks its clock how much time passed since the
+/// last update.
+pub trait Clock{
+    ///Returns the time in seconds which passed since the last call, or since the clock was
+    /// created for the first call.
+    fn tick(&mut self) -> f32;
+    ///Returns a copy of this clock for a copy of the tree, so the two trees don't take each others
+    /// time. A clock can keep parts of its state shared on purpose, like `ManualClock` does.
+    fn box_clone(&self) -> Box<dyn Clock + Send>;
+}
+
+///The clock a tree owns. The mutex keeps the tree `Sync` without asking the same of every clock.
+pub(crate) type TreeClock = Mutex<Box<dyn Clock + Send>>;
+
+///Wraps `clock` to be owned by a tree.
+pub(crate) fn tree_clock<C>(clock: C) -> TreeClock where C: Clock + Send + 'static{
+    Mutex::new(Box::new(clock))
+}
+
+///Returns a copy of `clock` for a copy of a tree.
+pub(crate) fn clone_clock(clock: &TreeClock) -> TreeClock{
+    Mutex::new(clock.lock().expect("failed to lock clock").box_clone())
+}
+
+///Measures the real time which passes. This is the default clock of a tree.
+#[derive(Clone, Debug)]
+pub struct RealClock{
+    last_tick: Instant,
+}
+
+impl RealClock{
+    ///Creates a clock which starts measuring now.
+    pub fn new() -> Self{
+        RealClock{
+            last_tick: Instant::now(),
+        }
+    }
+}
+
+impl Default for RealClock{
+    fn default() -> Self{
+        RealClock::new()
+    }
+}
+
+impl Clock for RealClock{
+    fn tick(&mut self) -> f32{
+        let delta = self.last_tick.elapsed().as_secs_f32();
+        self.last_tick = Instant::now();
+        delta
+    }
+
+    fn box_clone(&self) -> Box<dyn Clock + Send>{
+        Box::new(self.clone())
+    }
+}
+
+///A clock which only moves when it's told to, for tests and replays. All clones of a manual clock share
+/// the same time, so keep a clone of the clock you hand to a tree to advance it:
+///
+/// ```text
+/// let clock = ManualClock::new();
+/// tree.set_clock(clock.clone());
+/// clock.advance(0.016);
+/// tree.update();
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct ManualClock{
+    ///Time which was advanced, but not yet returned by `tick()`
+    pending: Arc<Mutex<f32>>,
+}
+
+impl ManualClock{
+    ///Creates a clock at which no time has passed yet.
+    pub fn new() -> Self{
+        ManualClock{
+            pending: Arc::new(Mutex::new(0.0)),
+        }
+    }
+
+    ///Lets `seconds` pass, they are returned by the next `tick()`.
+    pub fn advance(&self, seconds: f32){
+        *self.pending.lock().expect("failed to lock manual clock") += seconds;
+    }
+}
+
+impl Clock for ManualClock{
+    fn tick(&mut self) -> f32{
+        let mut pending = self.pending.lock().expect("failed to lock manual clock");
+        let delta = *pending;
+        *pending = 0.0;
+        delta
+    }
+
+    ///The copy shares the time with self, so advancing one clock advances both.
+    fn box_clone(&self) -> Box<dyn Clock + Send>{
+        Box::new(self.clone())
+    }
+}
+
+///A clock where every tick takes exactly the same time, no matter how much real time passed.
+#[derive(Clone, Copy, Debug)]
+pub struct SteppedClock{
+    step: f32,
+}
+
+impl SteppedClock{
+    ///Creates a clock which advances by `step` seconds per tick.
+    pub fn new(step: f32) -> Self{
+        SteppedClock{
+            step,
+        }
+    }
+}
+
+impl Clock for SteppedClock{
+    fn tick(&mut self) -> f32{
+        self.step
+    }
+
+    fn box_clone(&self) -> Box<dyn Clock + Send>{
+        Box::new(*self)
+    }
+}
+
+///Turns the real time which passed between two updates of a tree into the delta times the nodes
+/// are updated with.
+///
+/// By default every update advances the nodes once by the real time multiplied with the time scale.
+/// In fixed step mode the scaled time is collected instead and the nodes are advanced in steps of
+/// exactly the same length, as many as fit into the collected time.
+#[derive(Clone, Debug)]
+pub struct TimeStep{
+    ///Every real delta is multiplied with this
+    time_scale: f32,
+    ///While paused, the nodes are not updated at all
+    paused: bool,
+    ///Length of a fixed step in seconds, `None` for a variable step
+    fixed_step: Option<f32>,
+    ///Maximum number of fixed steps per update
+    max_steps: u32,
+    ///Scaled time which wasn't used by a fixed step yet
+    accumulator: f32,
+}
+
+impl TimeStep{
+    ///Creates an unpaused, variable time step with a time scale of 1.0.
+    pub fn new() -> Self{
+        TimeStep{
+            time_scale: 1.0,
+            paused: false,
+            fixed_step: None,
+            max_steps: 8,
+            accumulator: 0.0,
+        }
+    }
+
+    ///Returns the factor every real delta is multiplied with
+    pub fn time_scale(&self) -> f32{
+        self.time_scale
+    }
+
+    ///Sets the factor every real delta is multiplied with. 0.5 for instance runs everything at half
+    /// the speed, 0.0 stops the time, but still updates the nodes.
+    /// Returns an error if `time_scale` is negative or not finite, the time can't run backwards.
+    pub fn set_time_scale(&mut self, time_scale: f32) -> Result<(), NodeErrors>{
+        if !time_scale.is_finite() || time_scale < 0.0{
+            return Err(NodeErrors::InvalidTimeStep(format!("{} is no valid time scale", time_scale)));
+        }
+        self.time_scale = time_scale;
+        Ok(())
+    }
+
+    ///Returns true if the time is currently paused
+    pub fn is_paused(&self) -> bool{
+        self.paused
+    }
+
+    ///Pauses the time, nodes are not updated until `resume()` is called. The time which passes in
+    /// between is dropped.
+    pub fn pause(&mut self){
+        self.paused = true;
+    }
+
+    ///Resumes the time after a `pause()`
+    pub fn resume(&mut self){
+        self.paused = false;
+    }
+
+    ///Returns the length of a fixed step in seconds, or `None` if the step is variable
+    pub fn fixed_step(&self) -> Option<f32>{
+        self.fixed_step
+    }
+
+    ///Switches to fixed steps of `Some(step)` seconds, or back to variable steps with `None`.
+    /// Returns an error if `step` is not a positive, finite number of seconds.
+    pub fn set_fixed_step(&mut self, step: Option<f32>) -> Result<(), NodeErrors>{
+        if let Some(step) = step{
+            if !step.is_finite() || step <= 0.0{
+                return Err(NodeErrors::InvalidTimeStep(format!("{} is no valid fixed step", step)));
+            }
+        }
+        self.fixed_step = step;
+        self.accumulator = 0.0;
+        Ok(())
+    }
+
+    ///Returns how many fixed steps are done per update at most
+    pub fn max_steps(&self) -> u32{
+        self.max_steps
+    }
+
+    ///Sets how many fixed steps are done per update at most. If more time has been collected (for
+    /// instance after a very long frame), the rest is dropped, so a slow update can't cause an even
+    /// slower one.
+    pub fn set_max_steps(&mut self, max_steps: u32){
+        self.max_steps = max_steps;
+    }
+
+    ///Returns the deltas the nodes have to be updated with after `real_delta` seconds passed.
+    /// Can be empty, for instance while paused or if not enough time was collected for a fixed step.
+    pub fn advance(&mut self, real_delta: f32) -> Vec<f32>{
+        if self.paused{
+            return Vec::new();
+        }
+
+        let scaled_delta = real_delta * self.time_scale;
+        let step = match self.fixed_step{
+            Some(step) => step,
+            None => return vec![scaled_delta],
+        };
+
+        self.accumulator += scaled_delta;
+        let mut steps = Vec::new();
+        while self.accumulator >= step && (steps.len() as u32) < self.max_steps{
+            steps.push(step);
+            self.accumulator -= step;
+        }
+        //don't try to catch up with time we couldn't use
+        if self.accumulator >= step{
+            self.accumulator %= step;
+        }
+        steps
+    }
+}
+
+impl Default for TimeStep{
+    fn default() -> Self{
+        TimeStep::new()
+    }
+}
+
+#[cfg(test)]
+mod tests{
+    use super::TimeStep;
+
+    #[test]
+    fn variable_step_is_scaled(){
+        let mut time = TimeStep::new();
+        assert_eq!(time.advance(0.5), vec![0.5]);
+        time.set_time_scale(0.5).unwrap();
+        assert_eq!(time.advance(0.5), vec![0.25]);
+    }
+
+    #[test]
+    fn fixed_step_carries_leftover_time(){
+        let mut time = TimeStep::new();
+        time.set_fixed_step(Some(0.25)).unwrap();
+
+        assert_eq!(time.advance(0.375), vec![0.25]);
+        //0.125 was left from the last update
+        assert_eq!(time.advance(0.375), vec![0.25, 0.25]);
+        assert!(time.advance(0.125).is_empty());
+        assert_eq!(time.advance(0.125), vec![0.25]);
+    }
+
+    #[test]
+    fn max_steps_drop_extra_time(){
+        let mut time = TimeStep::new();
+        time.set_fixed_step(Some(0.25)).unwrap();
+        time.set_max_steps(2);
+
+        assert_eq!(time.advance(2.125), vec![0.25, 0.25]);
+        //the whole steps which didn't fit are gone, only the 0.125 which was left over is kept
+        assert_eq!(time.advance(0.125), vec![0.25]);
+        assert!(time.advance(0.125).is_empty());
+    }
+
+    #[test]
+    fn pause_drops_time(){
+        let mut time = TimeStep::new();
+        time.set_fixed_step(Some(0.25)).unwrap();
+        time.pause();
+
+        assert!(time.is_paused());
+        assert!(time.advance(1.0).is_empty());
+        time.resume();
+        assert!(time.advance(0.125).is_empty());
+        assert_eq!(time.advance(0.125), vec![0.25]);
+    }
+
+    #[test]
+    fn rejects_invalid_values(){
+        let mut time = TimeStep::new();
+
+        assert!(time.set_time_scale(-1.0).is_err());
+        assert!(time.set_time_scale(f32::NAN).is_err());
+        assert!(time.set_time_scale(f32::INFINITY).is_err());
+        assert_eq!(time.time_scale(), 1.0);
+        assert!(time.set_time_scale(0.0).is_ok());
+
+        assert!(time.set_fixed_step(Some(0.0)).is_err());
+        assert!(time.set_fixed_step(Some(-0.25)).is_err());
+        assert!(time.set_fixed_step(Some(f32::NAN)).is_err());
+        assert_eq!(time.fixed_step(), None);
+        assert!(time.set_fixed_step(None).is_ok());
+    }
+}
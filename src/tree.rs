@@ -1,15 +1,38 @@
 use std::collections::BTreeMap;
-use std::path::PathBuf;
-use std::time::Instant;
+use std::sync::Arc;
 use std;
 
 use node;
+use naming;
+use id::{IdTable, NodeId};
+use path::NodePath;
+use select;
+use iter;
+use visit;
+use time;
+use schedule::{self, ScheduleHandle};
 ///The errors which can appear when adding a new child
 #[derive(Debug)]
 pub enum NodeErrors {
     ///Appears if there is no child with the given search parameter
     NoSuchChild(String),
     NoNodeFound(String),
+    ///Appears if an operation can't be applied to the root node of a tree, like removing it
+    IsRootNode(String),
+    ///Appears if a node should be moved below itself or one of its own children
+    WouldCreateCycle(String),
+    ///Appears if a node should get a name which is already used by another node of the tree
+    NameTaken(String),
+    ///Appears if a name can't be used for a node, for instance because it is empty
+    InvalidName(String),
+    ///Appears if a string can't be parsed as a `NodePath`
+    InvalidPath(String),
+    ///Appears if a string can't be parsed as a `select::Selector`
+    InvalidSelector(String),
+    ///Appears if a job can't be scheduled, for instance because its interval is not positive
+    InvalidSchedule(String),
+    ///Appears if a `time::TimeStep` can't use a value, for instance a negative time scale
+    InvalidTimeStep(String),
 }
 
 ///Implements a compfy to_string methode
@@ -18,13 +41,20 @@ impl std::string::ToString for NodeErrors{
         match self{
             &NodeErrors::NoSuchChild(ref s) => s.clone(),
             &NodeErrors::NoNodeFound(ref s) => s.clone(),
+            &NodeErrors::IsRootNode(ref s) => s.clone(),
+            &NodeErrors::WouldCreateCycle(ref s) => s.clone(),
+            &NodeErrors::NameTaken(ref s) => s.clone(),
+            &NodeErrors::InvalidName(ref s) => s.clone(),
+            &NodeErrors::InvalidPath(ref s) => s.clone(),
+            &NodeErrors::InvalidSelector(ref s) => s.clone(),
+            &NodeErrors::InvalidSchedule(ref s) => s.clone(),
+            &NodeErrors::InvalidTimeStep(ref s) => s.clone(),
         }
     }
 }
 
 ///Describes a tree which can hold nodes of the type T.
 /// The tree also holds a registry of all its values with its paths.
-#[derive(Clone)]
 pub struct Tree<T: node::NodeContent + Clone, J: Clone, A: node::Attribute<J> + Clone>{
 
     ///The name of this tree. It is usally derived from the name of the root node supplyied to
@@ -34,17 +64,41 @@ pub struct Tree<T: node::NodeContent + Clone, J: Clone, A: node::Attribute<J> +
     ///Stores the path to every node of this tree, keyed by the nodes name.
     /// For instance a data set could look like this:
     ///
-    /// "Teddy", "Root/Cave/LeftSide/Teddy"
+    /// "Teddy", "/Cave/LeftSide/Teddy"
     ///
     /// In this case the teddy is At the root nodes child "Cave", which has a child LeftSide, which has the child Teddy
-    pub registry: BTreeMap<String, PathBuf>,
+    pub registry: BTreeMap<String, NodePath>,
     ///The root node of this tree
     pub root_node: node::Node<T, J, A>,
-    ///Keeps track of the last tick time.
-    last_tick: Instant,
+    ///Tells the tree how much time passed since the last update.
+    clock: time::TreeClock,
+    ///Turns the time between two updates into the delta time of the nodes
+    time: time::TimeStep,
+    ///Decides which name a new node gets
+    naming_policy: Arc<dyn naming::NamingPolicy + Send + Sync>,
+    ///Holds the `NodeId` of every node in the registry
+    ids: IdTable,
+    ///Merges consecutive jobs before they are executed, `None` until merging is enabled
+    job_merge: Option<node::MergeFn<J>>,
 
 }
 
+///Every clone gets its own copy of the clock, otherwise updating one tree would take the time of the other one.
+impl<T: node::NodeContent + Clone, J: Clone, A: node::Attribute<J> + Clone> Clone for Tree<T, J, A> {
+    fn clone(&self) -> Self{
+        Tree{
+            name: self.name.clone(),
+            registry: self.registry.clone(),
+            root_node: self.root_node.clone(),
+            clock: time::clone_clock(&self.clock),
+            time: self.time.clone(),
+            naming_policy: self.naming_policy.clone(),
+            ids: self.ids.clone(),
+            job_merge: self.job_merge,
+        }
+    }
+}
+
 ///Implements the base functions of `Tree`
 impl<T: node::NodeContent + Clone, J: Clone, A: node::Attribute<J> + Clone> Tree<T, J, A> {
 
@@ -55,19 +109,93 @@ impl<T: node::NodeContent + Clone, J: Clone, A: node::Attribute<J> + Clone> Tree
         let root_node = node::Node::new(root, root_attributes);
         let mut registry = BTreeMap::new();
         //add the root node to the registry
-        registry.insert(tree_name.clone(), PathBuf::from("/".to_string()));
+        registry.insert(tree_name.clone(), NodePath::root());
+        let mut ids = IdTable::new();
+        ids.insert(tree_name.clone());
 
         Tree{
             name: tree_name,
             registry: registry,
             root_node: root_node,
-            last_tick: Instant::now(),
+            clock: time::tree_clock(time::RealClock::new()),
+            time: time::TimeStep::new(),
+            naming_policy: Arc::new(naming::CounterSuffix::new()),
+            ids,
+            job_merge: None,
         }
     }
 
+    ///Creates a new tree with `root` as its root node. All children of `root` are kept and a
+    /// fresh registry is built for them. Like a tree created with `new()`, the tree starts with a
+    /// `time::RealClock` and the default time step and naming policy.
+    /// Returns an error if a child has no valid name, if it is stored under another name than its own,
+    /// or if two nodes have the same name, since the registry finds nodes by unique names.
+    pub fn from_node(root: node::Node<T, J, A>) -> Result<Self, NodeErrors>{
+        let tree_name = root.get_name();
+        let mut registry = BTreeMap::new();
+        registry.insert(tree_name.clone(), NodePath::root());
+        register_children(&root, &NodePath::root(), &mut registry)?;
+        let mut ids = IdTable::new();
+        for (name, _) in registry.iter(){
+            ids.insert(name.clone());
+        }
+
+        Ok(Tree{
+            name: tree_name,
+            registry,
+            root_node: root,
+            clock: time::tree_clock(time::RealClock::new()),
+            time: time::TimeStep::new(),
+            naming_policy: Arc::new(naming::CounterSuffix::new()),
+            ids,
+            job_merge: None,
+        })
+    }
+
+    ///Converts this tree into a tree of other content and attribute types. `convert` is called for
+    /// every node and returns its new value and attributes. The hierarchy, the names and the ids of
+    /// all nodes stay exactly the same, jobs and controllers are not carried over. The new tree keeps
+    /// the time step and a copy of the clock.
+    pub fn map<T2, J2, A2, F>(&self, mut convert: F) -> Tree<T2, J2, A2>
+    where T2: node::NodeContent + Clone, J2: Clone, A2: node::Attribute<J2> + Clone, F: FnMut(&node::Node<T, J, A>) -> (T2, A2){
+        let mapped_root = self.root_node.map(&mut convert);
+        self.with_root(mapped_root)
+    }
+
+    ///Same as `map()`, but `convert` can fail. Returns the first error `convert` returned, if any.
+    pub fn try_map<T2, J2, A2, E, F>(&self, mut convert: F) -> Result<Tree<T2, J2, A2>, E>
+    where T2: node::NodeContent + Clone, J2: Clone, A2: node::Attribute<J2> + Clone, F: FnMut(&node::Node<T, J, A>) -> Result<(T2, A2), E>{
+        let mapped_root = self.root_node.try_map(&mut convert)?;
+        Ok(self.with_root(mapped_root))
+    }
+
+    ///Creates a tree with the registry, ids, naming policy, time step and a copy of the clock of self, but
+    /// the other `root` node. `root` has to have the same hierarchy and names as the root node of self.
+    /// Jobs of the new type are not merged.
+    fn with_root<T2, J2, A2>(&self, root: node::Node<T2, J2, A2>) -> Tree<T2, J2, A2>
+    where T2: node::NodeContent + Clone, J2: Clone, A2: node::Attribute<J2> + Clone{
+        Tree{
+            name: self.name.clone(),
+            registry: self.registry.clone(),
+            root_node: root,
+            clock: time::clone_clock(&self.clock),
+            time: self.time.clone(),
+            naming_policy: self.naming_policy.clone(),
+            ids: self.ids.clone(),
+            job_merge: None,
+        }
+    }
+
+    ///Sets the `policy` which decides under which name new nodes are added to this tree. This
+    /// affects `add()`, `add_at_root()` and the nodes added while joining another tree.
+    /// The default is `naming::CounterSuffix`.
+    pub fn set_naming_policy<P>(&mut self, policy: P) where P: naming::NamingPolicy + Send + Sync + 'static{
+        self.naming_policy = Arc::new(policy);
+    }
+
     ///Same as `add()` but the `new_child` will atomaticly be added to the root node
     pub fn add_at_root(&mut self, new_child: T, attributes: Option<A>)->
-    Result<String, NodeErrors>{
+    Result<NodeId, NodeErrors>{
         //get the root name from the tree name (see implementation of the new() function)
         let root_name = self.name.clone();
         //now add it there
@@ -76,16 +204,16 @@ impl<T: node::NodeContent + Clone, J: Clone, A: node::Attribute<J> + Clone> Tree
 
     ///Adds a `new_child` at a `parent` node with `Some(attributes)` set
     /// (or the default attributes if None is supplied).
-    /// Returns the name under which it was addded as `Ok(name)`
-    /// or an `Err(e)` if something went wrong.
+    /// Returns the id of the new node as `Ok(id)` or an `Err(e)` if something went wrong.
+    /// The name under which the node was added can be retrieved with `name_of(id)`.
     pub fn add(&mut self, new_child: T, parent_name: String, attributes: Option<A>)->
-    Result<String, NodeErrors>{
+    Result<NodeId, NodeErrors>{
         //First we have to get the node in this tree with the searched name.
         // If this is successful, we test the new_child's name for being unique.
         // If not, we change the name to something unique for the `T`.
         // After this we add the unique named node to the parent.
         // The we add the new, unique name as well as the new path to the registry.
-        //Finally we return the unique name in an Ok(k).
+        //Finally we return the id of the new node in an Ok(k).
 
         let parent_path = {
             match self.registry.get(&parent_name){
@@ -105,37 +233,20 @@ impl<T: node::NodeContent + Clone, J: Clone, A: node::Attribute<J> + Clone> Tree
         };
 
         //Testing the childs name
-        let unique_name: String = {
-            //Checking for the name. NOTE: to make the system correctly working, we have to
-            // replace dots "." with something. Otherwise the name would make the node's path to a file
-            // which would screw around with the registry
-            match self.registry.get(&new_child.get_name().replace(".", "_")){
-                Some(_) => {
-                    //The name is already in there, we have to make a new unique one
-                    //Currently we use the easiest way to make it unique by adding an incrementing
-                    //number to the end of this name until we can't find a entry with this name+number
-                    //then using this name+number as unique name.
-                    let mut append_number = 0;
-                    let initial_node_name = new_child.get_name().replace(".", "_");
-                    while self.registry.get(
-                                &(initial_node_name.clone() + "_" + &append_number.to_string())
-                                            ).is_some()
-                    {
-                        append_number +=1;
-                    }
-                    //after finding a good enough number, returning the name
-                    initial_node_name.clone() + "_" + &append_number.to_string()
-                },
-                None => {
-                    //the name is already unique returing it
-                    new_child.get_name().replace(".", "_").clone()
-                }
-            }
+        let wanted_name = new_child.get_name();
+        let unique_name = {
+            let registry = &self.registry;
+            self.naming_policy.unique_name(&wanted_name, &|name| registry.contains_key(name))?
         };
 
+        //The policy could be a custom one, so don't trust it blindly
+        {
+            let registry = &self.registry;
+            naming::check_new_name(&unique_name, &|name| registry.contains_key(name))?;
+        }
+
         //Pre constructing the new node path.
-        let mut new_path = parent_path.clone();
-        new_path.push(unique_name.clone());
+        let new_path = parent_path.join(&unique_name);
 
         //something could be wrong with the path or so (shouldnt but still I don't like unwraps())
         match self.get_from_path(&parent_path){
@@ -163,33 +274,285 @@ impl<T: node::NodeContent + Clone, J: Clone, A: node::Attribute<J> + Clone> Tree
         // the right parent node.So we can add the path to the registry.
         self.registry.insert(unique_name.clone(), new_path);
 
-        Ok(unique_name)
+        Ok(self.ids.insert(unique_name))
+    }
+
+    ///Removes the node with this `name` and all of its children from the tree. Every removed node
+    /// is dropped from the registry as well.
+    /// Returns the detached node as `Ok(node)` or an `Err(e)` if there is no such node or if `name`
+    /// is the root node.
+    pub fn remove(&mut self, name: &str) -> Result<node::Node<T, J, A>, NodeErrors>{
+        let path = {
+            match self.registry.get(name){
+                Some(path) => path.clone(),
+                None => return Err(NodeErrors::NoSuchChild(
+                    String::from("Could not find ") + name + " in tree!"
+                )),
+            }
+        };
+
+        //Take the node out of its parents children. This detaches the whole subtree at once.
+        let removed = self.take_from_parent(name, &path)?;
+
+        //now clean up the registry for the removed node and all of its children
+        let mut removed_names = Vec::new();
+        removed.collect_names(&mut removed_names);
+        for removed_name in removed_names.iter(){
+            self.registry.remove(removed_name);
+            self.ids.remove(removed_name);
+        }
+
+        Ok(removed)
     }
 
-    ///Updates the whole tree
+    ///Moves the node with this `name` (including its children, jobs and controller) from its current
+    /// parent to the node `new_parent`. The names of all moved nodes stay the same, only their
+    /// paths in the registry change.
+    /// Returns an `Err(e)` if one of the nodes could not be found, if `name` is the root node or
+    /// if `new_parent` is `name` itself or one of its children.
+    pub fn move_node(&mut self, name: &str, new_parent: &str) -> Result<(), NodeErrors>{
+        let path = {
+            match self.registry.get(name){
+                Some(path) => path.clone(),
+                None => return Err(NodeErrors::NoSuchChild(
+                    String::from("Could not find ") + name + " in tree!"
+                )),
+            }
+        };
+
+        let new_parent_path = {
+            match self.registry.get(new_parent){
+                Some(path) => path.clone(),
+                None => return Err(NodeErrors::NoSuchChild(
+                    String::from("Could not find ") + new_parent + " in tree!"
+                )),
+            }
+        };
+
+        if path.parent().is_none(){
+            return Err(NodeErrors::IsRootNode(
+                String::from("Can't move the root node ") + name
+            ));
+        }
+
+        //If the new parent is somewhere below the moved node we would cut the moved subtree
+        // off the tree, so don't do that.
+        if new_parent_path.starts_with(&path){
+            return Err(NodeErrors::WouldCreateCycle(
+                String::from("Can't move ") + name + " below itself at " + new_parent
+            ));
+        }
+
+        //detach the node, but keep everything it contains, then add it to its new parent
+        let moved = self.take_from_parent(name, &path)?;
+        self.get_from_path(&new_parent_path)?.get_children_mut().insert(name.to_string(), moved);
+
+        //Finally rewrite the paths of the moved node and all of its children.
+        let new_path = new_parent_path.join(name);
+        self.relocate_paths(&path, &new_path);
+
+        Ok(())
+    }
+
+    ///Renames the node `old` to `new`. This changes the name of the node itself, the key under
+    /// which its parent stores it, the registry entry and the paths of all its children.
+    /// Returns an `Err(e)` if there is no node called `old`, if `new` is already taken
+    /// or if `new` can't be used as a node name.
+    pub fn rename(&mut self, old: &str, new: &str) -> Result<(), NodeErrors>{
+        if !naming::is_valid_name(new){
+            return Err(NodeErrors::InvalidName(
+                String::from("Can't use ") + new + " as a node name!"
+            ));
+        }
+
+        let path = {
+            match self.registry.get(old){
+                Some(path) => path.clone(),
+                None => return Err(NodeErrors::NoSuchChild(
+                    String::from("Could not find ") + old + " in tree!"
+                )),
+            }
+        };
+
+        if old == new{
+            return Ok(());
+        }
+
+        if self.registry.contains_key(new){
+            return Err(NodeErrors::NameTaken(
+                String::from("There is already a node called ") + new + " in the tree!"
+            ));
+        }
+
+        let new_path = {
+            match path.parent(){
+                Some(parent_path) => {
+                    //Re-insert the node under its new name in the parents children
+                    let mut renamed = self.take_from_parent(old, &path)?;
+                    renamed.set_name(new.to_string());
+                    self.get_from_path(&parent_path)?.get_children_mut().insert(new.to_string(), renamed);
+                    parent_path.join(new)
+                },
+                None => {
+                    //The root is not stored in any parent, but the tree is named after it
+                    self.root_node.set_name(new.to_string());
+                    self.name = new.to_string();
+                    path.clone()
+                }
+            }
+        };
+
+        self.registry.remove(old);
+        self.registry.insert(new.to_string(), path.clone());
+        self.ids.rename(old, new);
+        self.relocate_paths(&path, &new_path);
+
+        Ok(())
+    }
+
+    ///Replaces `old_path` with `new_path` for every registry entry which starts with `old_path`.
+    fn relocate_paths(&mut self, old_path: &NodePath, new_path: &NodePath){
+        for (_, node_path) in self.registry.iter_mut(){
+            let relocated = match node_path.strip_prefix(old_path){
+                Some(relative) => new_path.concat(&relative),
+                None => continue,
+            };
+            *node_path = relocated;
+        }
+    }
+
+    ///Removes the node with this `name` and all of its children from the tree and returns them
+    /// as a new, independent tree with `name` as root node. This is the reverse of `join()`.
+    /// The new tree uses the same naming policy, time step and a copy of the clock of `self`, but new ids:
+    /// the ids of the removed nodes become stale.
+    /// Returns an `Err(e)` under the same conditions as `remove()`, or like `from_node()` if the removed
+    /// nodes were not named uniquely, for instance after children were added to `root_node` directly.
+    pub fn split_off(&mut self, name: &str) -> Result<Self, NodeErrors>{
+        let new_root = self.remove(name)?;
+        let mut new_tree = Tree::from_node(new_root)?;
+        //keep naming the nodes and handling the time the same way
+        new_tree.naming_policy = self.naming_policy.clone();
+        new_tree.job_merge = self.job_merge;
+        new_tree.time = self.time.clone();
+        new_tree.clock = time::clone_clock(&self.clock);
+        Ok(new_tree)
+    }
+
+    ///Returns the time step, which controls the time scale, pausing and fixed steps of `update()`.
+    pub fn time_step(&self) -> &time::TimeStep{
+        &self.time
+    }
+
+    ///Returns the time step mutable, for instance to pause the tree or to switch to fixed steps.
+    pub fn time_step_mut(&mut self) -> &mut time::TimeStep{
+        &mut self.time
+    }
+
+    ///Sets the `clock` which tells the tree how much time passed since the last update. The default
+    /// is a `time::RealClock`. Every clone of this tree gets its own copy of the clock.
+    pub fn set_clock<C>(&mut self, clock: C) where C: time::Clock + Send + 'static{
+        self.clock = time::tree_clock(clock);
+    }
+
+    ///Updates the whole tree. The time since the last update is taken from the clock of the tree.
     pub fn update(&mut self){
         //Get the new time since the last tick
-        let time = self.last_tick.elapsed().subsec_nanos() as f32 / 1_000_000_000.0;
-        self.last_tick = Instant::now();
+        let real_delta = self.clock.lock().expect("failed to lock clock").tick();
+        self.update_with_delta(real_delta);
+    }
+
+    ///Updates the whole tree as if `real_delta` seconds passed since the last update, the clock of the
+    /// tree is not asked. The delta is passed through the time step of the tree, which decides how
+    /// often and with which delta time the nodes are updated.
+    pub fn update_with_delta(&mut self, real_delta: f32){
+        //depending on the time step this can be no update at all, or several fixed steps
+        for delta in self.time.advance(real_delta){
+            let context = node::UpdateContext{
+                delta,
+                merge: self.job_merge,
+            };
+            self.root_node.update_carried(&context, &[]);
+            //nothing is above the root node
+            self.root_node.take_upward();
+        }
+    }
 
-        self.root_node.update(time, &Vec::new());
+    ///Lets every node merge consecutive jobs with `JobMerge::merge()` before executing them, so fewer
+    /// jobs are executed and sent down to the children. Off by default.
+    pub fn enable_job_merging(&mut self) where J: node::JobMerge{
+        self.job_merge = Some(J::merge);
     }
 
-    ///Returns a mutable reference to a child by its `path`
-    fn get_from_path(&mut self, path: &PathBuf) -> Result<&mut node::Node<T, J, A>, NodeErrors>{
-        //To get a node we walk down the path by searching (.pop()) for the last element of the vector
-        //which we get by transforming the path into custom_path_iter().
-        //the node get the changes path (one item smaller) and searches in its own nodes for a node
-        //of the "this time" last elment etc. till we got the right node. This one is returned till
-        //here and then returned to the caller.
-        let mut reverse_path = custom_path_iter(path);
-
-        //Test if the first is the root node, if yes, add to it, otherwise go down the root node
-        if reverse_path.len() == 0{
-            return Ok(&mut self.root_node);
+    ///Executes every job on its own again, see `enable_job_merging()`.
+    pub fn disable_job_merging(&mut self){
+        self.job_merge = None;
+    }
+
+    ///Schedules `job` on the node `name`, it is executed as described by `schedule`. The times are
+    /// measured in the delta time of the nodes, so they are scaled and paused with the tree.
+    /// Returns a handle which can cancel the job.
+    pub fn schedule(&mut self, name: &str, job: J, schedule: schedule::Schedule) -> Result<ScheduleHandle, NodeErrors>{
+        let (node_id, node) = self.scheduling_node(name)?;
+        let id = node.schedule(job, schedule)?;
+        Ok(ScheduleHandle::new(node_id, id))
+    }
+
+    ///Spreads `job` on the node `name` over `duration` seconds. Every update the part of the job
+    /// which belongs to the delta time is executed, for instance translating by 5 units over 2 seconds
+    /// translates by 0.25 units in an update with a delta of 0.1 seconds.
+    pub fn schedule_over(&mut self, name: &str, job: J, duration: f32) -> Result<ScheduleHandle, NodeErrors>
+    where J: schedule::JobFraction{
+        let (node_id, node) = self.scheduling_node(name)?;
+        let id = node.schedule_over(job, duration)?;
+        Ok(ScheduleHandle::new(node_id, id))
+    }
+
+    ///Returns the id and the node `name`, or an error if there is no such node.
+    fn scheduling_node(&mut self, name: &str) -> Result<(NodeId, &mut node::Node<T, J, A>), NodeErrors>{
+        let (node_id, path) = match (self.ids.id_of(name), self.registry.get(name)){
+            (Some(node_id), Some(path)) => (node_id, path.clone()),
+            _ => return Err(NodeErrors::NoSuchChild(
+                String::from("Could not find ") + name + " in tree!"
+            )),
+        };
+        Ok((node_id, self.get_from_path(&path)?))
+    }
+
+    ///Cancels the scheduled job `handle` points to. Returns false if the job already finished, was
+    /// cancelled before or its node was removed.
+    pub fn cancel(&mut self, handle: ScheduleHandle) -> bool{
+        match self.get_mut(handle.node()){
+            Some(node) => node.cancel_scheduled(handle.id()),
+            None => false,
         }
+    }
+
+    ///Returns a mutable reference to a child by its `path`
+    fn get_from_path(&mut self, path: &NodePath) -> Result<&mut node::Node<T, J, A>, NodeErrors>{
+        //The path starts below the root node, so the root node can walk down the whole path
+        self.root_node.get_node(path)
+    }
+
+    ///Removes the node `name` at `path` from its parents children and returns it. The registry
+    /// is not touched.
+    fn take_from_parent(&mut self, name: &str, path: &NodePath) -> Result<node::Node<T, J, A>, NodeErrors>{
+        //The root node has no parent we could detach it from
+        let parent_path = {
+            match path.parent(){
+                Some(parent) => parent,
+                None => return Err(NodeErrors::IsRootNode(
+                    String::from("Can't detach the root node ") + name
+                )),
+            }
+        };
 
-        self.root_node.get_node(&mut reverse_path)
+        let parent = self.get_from_path(&parent_path)?;
+        match parent.get_children_mut().remove(name){
+            Some(node) => Ok(node),
+            None => Err(NodeErrors::NoNodeFound(
+                String::from("Could not find ") + name + " in its parents children"
+            )),
+        }
     }
 
     ///Returns a node with this `name`
@@ -211,6 +574,232 @@ impl<T: node::NodeContent + Clone, J: Clone, A: node::Attribute<J> + Clone> Tree
         }
     }
 
+    ///Returns the node at `path`, or `None` if there is no node at this location.
+    pub fn get_node_at(&mut self, path: &NodePath) -> Option<&mut node::Node<T, J, A>>{
+        self.get_from_path(path).ok()
+    }
+
+    ///Returns the path of the node with this `name`
+    pub fn path_of(&self, name: &str) -> Option<&NodePath>{
+        self.registry.get(name)
+    }
+
+    ///Returns a reference to the node `id` points to, or `None` if the id is stale.
+    pub fn get(&self, id: NodeId) -> Option<&node::Node<T, J, A>>{
+        let path = self.registry.get(self.ids.name_of(id)?)?;
+        self.root_node.get_node_ref(path).ok()
+    }
+
+    ///Returns a mutable reference to the node `id` points to, or `None` if the id is stale.
+    pub fn get_mut(&mut self, id: NodeId) -> Option<&mut node::Node<T, J, A>>{
+        let name = self.ids.name_of(id)?.to_string();
+        self.get_node(&name)
+    }
+
+    ///Returns the name of the node `id` points to, or `None` if the id is stale.
+    pub fn name_of(&self, id: NodeId) -> Option<&str>{
+        self.ids.name_of(id)
+    }
+
+    ///Returns the id of the node with this `name`
+    pub fn id_of(&self, name: &str) -> Option<NodeId>{
+        self.ids.id_of(name)
+    }
+
+    ///Returns true if `id` still points to a node of this tree
+    pub fn contains(&self, id: NodeId) -> bool{
+        self.ids.name_of(id).is_some()
+    }
+
+    ///Returns the name of a node which was just added under `id`.
+    pub(crate) fn name_of_added(&self, id: NodeId) -> Result<String, NodeErrors>{
+        match self.ids.name_of(id){
+            Some(name) => Ok(name.to_string()),
+            None => Err(NodeErrors::NoNodeFound(String::from("Could not find the newly added node"))),
+        }
+    }
+
+    ///Returns the names of all nodes which are matched by the selector `pattern`, for instance
+    /// `RootNode/*/Teddy` or `**/Light*`. See `select::Selector` for the syntax.
+    /// Returns an `Err(e)` if the pattern is invalid.
+    pub fn select(&self, pattern: &str) -> Result<Vec<String>, NodeErrors>{
+        let selector = select::Selector::parse(pattern)?;
+        let mut selected = Vec::new();
+        select::collect_selected(&self.root_node, &selector, &selector.start(), &mut selected);
+        Ok(selected)
+    }
+
+    ///Calls `visitor` with every node which is matched by the selector `pattern`. Parents are
+    /// visited before their children. Returns how many nodes were visited or an `Err(e)` if the
+    /// pattern is invalid.
+    pub fn select_mut<F>(&mut self, pattern: &str, mut visitor: F) -> Result<usize, NodeErrors>
+    where F: FnMut(&mut node::Node<T, J, A>){
+        let selector = select::Selector::parse(pattern)?;
+        let start = selector.start();
+        Ok(select::visit_selected(&mut self.root_node, &selector, &start, &mut visitor))
+    }
+
+    ///Returns an iterator over all nodes of this tree, depth first. Every node is returned before its
+    /// children. Yields the path, the depth (0 for the root node) and the node.
+    pub fn iter(&self) -> iter::Iter<'_, T, J, A>{
+        self.root_node.iter()
+    }
+
+    ///Same as `iter()`, but gives mutable access to everything except the children of each node.
+    pub fn iter_mut(&mut self) -> iter::IterMut<'_, T, J, A>{
+        self.root_node.iter_mut()
+    }
+
+    ///Returns an iterator over all nodes of this tree, breadth first.
+    pub fn iter_bfs(&self) -> iter::BfsIter<'_, T, J, A>{
+        self.root_node.iter_bfs()
+    }
+
+    ///Returns an iterator over all nodes of this tree, depth first. Every node is returned after its
+    /// children, the root node is returned last.
+    pub fn iter_post_order(&self) -> iter::PostOrderIter<'_, T, J, A>{
+        self.root_node.iter_post_order()
+    }
+
+    ///Drives the `visitor` over all nodes of this tree, starting at the root node with depth 0.
+    /// Returns `VisitControl::Stop` if the visitor stopped the walk, `VisitControl::Continue` otherwise.
+    pub fn visit<V>(&mut self, visitor: &mut V) -> visit::VisitControl where V: visit::TreeVisitor<T, J, A>{
+        self.root_node.visit(visitor)
+    }
+
+    ///Returns the first node (depth first) for which `predicate` returns true, or `None`.
+    pub fn find<F>(&self, mut predicate: F) -> Option<&node::Node<T, J, A>> where F: FnMut(&node::Node<T, J, A>) -> bool{
+        self.iter().map(|(_, _, node)| node).find(|node| predicate(node))
+    }
+
+    ///Returns every node for which `predicate` returns true. Parents are returned before their children.
+    pub fn find_all<F>(&self, mut predicate: F) -> Vec<&node::Node<T, J, A>> where F: FnMut(&node::Node<T, J, A>) -> bool{
+        self.iter().map(|(_, _, node)| node).filter(|node| predicate(node)).collect()
+    }
+
+    ///Returns how many nodes there are for which `predicate` returns true.
+    pub fn count_where<F>(&self, mut predicate: F) -> usize where F: FnMut(&node::Node<T, J, A>) -> bool{
+        self.iter().filter(|&(_, _, node)| predicate(node)).count()
+    }
+
+    ///Removes every node for which `predicate` returns false, together with all of its children.
+    /// The children of a removed node are not evaluated. The root node is always kept.
+    /// Returns how many nodes were removed.
+    pub fn retain<F>(&mut self, mut predicate: F) -> usize where F: FnMut(&node::Node<T, J, A>) -> bool{
+        let mut removed_names = Vec::new();
+        self.root_node.retain_children(&mut predicate, &mut removed_names);
+
+        for removed_name in removed_names.iter(){
+            self.registry.remove(removed_name);
+            self.ids.remove(removed_name);
+        }
+        removed_names.len()
+    }
+
+    ///Computes a value for every node from the values of its children, starting at the leaves. This is
+    /// the upward counterpart of the jobs, for instance to sum up bounding boxes or count lights.
+    /// `fold` gets a node and the results of its children (ordered by name) and returns the result
+    /// for the node. Returns the results of all nodes keyed by their names.
+    pub fn fold_up<R, F>(&self, mut fold: F) -> BTreeMap<String, R> where F: FnMut(&node::Node<T, J, A>, &[R]) -> R{
+        let mut results = BTreeMap::new();
+        let root_result = self.root_node.fold_up_into(&mut fold, &mut results);
+        results.insert(self.root_node.get_name(), root_result);
+        results
+    }
+
+    ///Returns every node whose attributes match the `comparer`, see `node::Attribute::compare()`.
+    /// Parents are returned before their children.
+    pub fn find_by_attributes(&self, comparer: &A::Comparer) -> Vec<&node::Node<T, J, A>>{
+        let mut found = Vec::new();
+        self.root_node.find_by_attributes(comparer, &mut found);
+        found
+    }
+
+    ///Returns the first node whose attributes match the `comparer`, or `None` if there is none.
+    pub fn find_first_by_attributes(&self, comparer: &A::Comparer) -> Option<&node::Node<T, J, A>>{
+        self.root_node.find_first_by_attributes(comparer)
+    }
+
+    ///Calls `visitor` with every node whose attributes match the `comparer`. Returns how many nodes
+    /// were visited.
+    pub fn for_each_by_attributes_mut<F>(&mut self, comparer: &A::Comparer, mut visitor: F) -> usize
+    where F: FnMut(&mut node::Node<T, J, A>){
+        self.root_node.for_each_by_attributes_mut(comparer, &mut visitor)
+    }
+
+    ///Adds `job` in `mode` to every node whose name path matches the selector `pattern`, see
+    /// `select()`. With `JobMode::SelfOnly` only the matching nodes are changed, instead of pushing
+    /// the job through the whole tree. Returns how many nodes got the job.
+    ///
+    /// If a node and one of its children match and the job is propagated, the child executes it twice.
+    pub fn send_to_selected(&mut self, pattern: &str, job: J, mode: node::JobMode) -> Result<usize, NodeErrors>{
+        self.select_mut(pattern, |node| node.add_job_with_mode(job.clone(), mode))
+    }
+
+    ///Same as `send_to_selected()`, but sends `job` to every node whose attributes match the `comparer`.
+    pub fn send_to_matching(&mut self, comparer: &A::Comparer, job: J, mode: node::JobMode) -> usize{
+        self.for_each_by_attributes_mut(comparer, |node| node.add_job_with_mode(job.clone(), mode))
+    }
+
+    ///Returns the name of the parent of the node `name`, or `None` if there is no such node or if
+    /// it is the root node.
+    pub fn parent_of(&self, name: &str) -> Option<String>{
+        let parent_path = self.registry.get(name)?.parent()?;
+        Some(self.name_at(&parent_path))
+    }
+
+    ///Returns an iterator over the names of all ancestors of the node `name`, starting with its
+    /// parent and ending with the root node. Returns `None` if there is no such node.
+    pub fn ancestors(&self, name: &str) -> Option<iter::Ancestors>{
+        let path = self.registry.get(name)?;
+        Some(iter::Ancestors::new(path, self.root_node.get_name()))
+    }
+
+    ///Returns how many levels the node `name` is below the root node (0 for the root node itself),
+    /// or `None` if there is no such node.
+    pub fn depth_of(&self, name: &str) -> Option<usize>{
+        self.registry.get(name).map(|path| path.depth())
+    }
+
+    ///Returns the names of all other children of the parent of the node `name`, or `None` if there
+    /// is no such node. The root node has no siblings.
+    pub fn siblings(&self, name: &str) -> Option<Vec<String>>{
+        let parent_path = match self.registry.get(name)?.parent(){
+            Some(parent_path) => parent_path,
+            None => return Some(Vec::new()),
+        };
+
+        let parent = self.root_node.get_node_ref(&parent_path).ok()?;
+        Some(
+            parent.get_children().keys()
+            .filter(|child_name| child_name.as_str() != name)
+            .cloned()
+            .collect()
+        )
+    }
+
+    ///Returns the name of the deepest node which has both `a` and `b` below it. If one of them is
+    /// below the other, this is the upper one. Returns `None` if one of the nodes doesn't exist.
+    pub fn lowest_common_ancestor(&self, a: &str, b: &str) -> Option<String>{
+        let path_a = self.registry.get(a)?;
+        let path_b = self.registry.get(b)?;
+
+        let common_length = path_a.segments().iter()
+            .zip(path_b.segments().iter())
+            .take_while(|&(segment_a, segment_b)| segment_a == segment_b)
+            .count();
+        let common_path = NodePath::from_segments(path_a.segments()[..common_length].iter().cloned());
+        Some(self.name_at(&common_path))
+    }
+
+    ///Returns the name of the node at `path`, the root node has the empty path.
+    fn name_at(&self, path: &NodePath) -> String{
+        match path.name(){
+            Some(name) => name.to_string(),
+            None => self.root_node.get_name(),
+        }
+    }
+
     ///Returns true if this Tree contains a node with this name
     pub fn has_node(&self, node_name: &str) -> bool{
         self.registry.contains_key(&String::from(node_name))
@@ -227,20 +816,22 @@ impl<T: node::NodeContent + Clone, J: Clone, A: node::Attribute<J> + Clone> Tree
 
     ///Merges `self` into `tree` at the node with a `name`. Returns Ok(k) if
     /// everything went all right or Err(e) if something went wrong.
-    /// NOTE: All values and attributes are cloned.
+    /// NOTE: All values and attributes are cloned. The names of the added nodes are chosen by the
+    /// naming policy of `self`.
     pub fn join(&mut self, tree: &Self, name: &str) -> Result<(),NodeErrors>{
 
         //Try to get the root node, add it at "name", get the actual returning name, add the children there etc
-        let new_root_name = {
+        let new_root_id = {
             match self.add(
                tree.root_node.get_value().clone(),
                name.to_string(),
                Some(tree.root_node.get_attrib().clone()),
            ){
-               Ok(new_name) => new_name,
+               Ok(new_id) => new_id,
                Err(r) => return Err(r),
            }
        };
+       let new_root_name = self.name_of_added(new_root_id)?;
 
 
        let mut return_val = Ok(());
@@ -272,40 +863,62 @@ impl<T: node::NodeContent + Clone, J: Clone, A: node::Attribute<J> + Clone> Tree
     pub fn print_registry(&self){
         println!("Current Registry:", );
         for (k, i) in self.registry.iter(){
-            println!("\t {} -> \n \t\t{:?}", k, i);
+            println!("\t {} -> \n \t\t{}", k, i);
         }
         println!("=====\n Tree has {} entries!\n =====", self.registry.len());
     }
 
 }
 
-///Generates a vector which holds the root of an path as the last element and the last node as the
-/// the first element.
-///For instance: \n
-/// `"/home/teddy/bear"` becomes an vector of `vec!["bear", "teddy", "home"]`.
-fn custom_path_iter(path: &PathBuf) -> Vec<String>{
+///Updating on several threads needs everything a node contains to be sendable
+#[cfg(feature = "parallel")]
+impl<T, J, A> Tree<T, J, A>
+where T: node::NodeContent + Clone + Send, J: Clone + Send + Sync, A: node::Attribute<J> + Clone + Send{
 
-    let mut mut_parents = path.clone();
+    ///Same as `update()`, but independent subtrees are updated on several threads, one per available
+    /// cpu core. See `node::Node::update_parallel()` for how the work is spread.
+    pub fn update_parallel(&mut self){
+        let real_delta = self.clock.lock().expect("failed to lock clock").tick();
+        self.update_with_delta_parallel(real_delta);
+    }
 
+    ///Same as `update_with_delta()`, but independent subtrees are updated on several threads.
+    pub fn update_with_delta_parallel(&mut self, real_delta: f32){
+        let threads = match std::thread::available_parallelism(){
+            Ok(count) => count.get(),
+            Err(_) => 1,
+        };
 
-    let mut is_some = true;
-    let mut wrong_way_vec = Vec::new();
+        for delta in self.time.advance(real_delta){
+            let context = node::UpdateContext{
+                delta,
+                merge: self.job_merge,
+            };
+            self.root_node.update_parallel_carried(&context, &[], threads);
+            self.root_node.take_upward();
+        }
+    }
+}
 
-    while is_some {
-        let parent = {
-            match mut_parents.file_stem(){
-                Some(e) =>{
-                    e.to_str().unwrap().to_string()
-                },
-                None => {
-                    break;
-                }
-            }
-        };
-        //add the valid parent to the vector
-        wrong_way_vec.push(parent);
-        //now pop out the parent for the next one
-        is_some = mut_parents.pop();
+///Adds the paths of all children of `node` (which is located at `path`) to the `registry`.
+/// Returns an error for the first child which can't be registered, see `Tree::from_node()`.
+fn register_children<T, J, A>(
+    node: &node::Node<T, J, A>,
+    path: &NodePath,
+    registry: &mut BTreeMap<String, NodePath>
+) -> Result<(), NodeErrors> where T: node::NodeContent + Clone, J: Clone, A: node::Attribute<J> + Clone{
+    for (child_name, child) in node.get_children().iter(){
+        //paths use the key, but jobs for single children are sent by the name of the node
+        if child.get_name() != *child_name{
+            return Err(NodeErrors::InvalidName(
+                String::from("The node called ") + &child.get_name() + " is stored as " + child_name + "!"
+            ));
+        }
+        naming::check_new_name(child_name, &|taken| registry.contains_key(taken))?;
+
+        let child_path = path.join(child_name);
+        registry.insert(child_name.clone(), child_path.clone());
+        register_children(child, &child_path, registry)?;
     }
-    wrong_way_vec
+    Ok(())
 }
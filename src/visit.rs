@@ -0,0 +1,26 @@
+use node;
+
+///Tells the walk what to do after a `TreeVisitor` entered a node.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum VisitControl{
+    ///Walk on, including the children of the node
+    Continue,
+    ///Walk on, but skip the children of the node. `exit()` is still called for the node.
+    SkipChildren,
+    ///Stop the whole walk immediately. No further `enter()` or `exit()` is called, not even for
+    /// the node which returned `Stop` and its parents.
+    Stop,
+}
+
+///Can be driven over a tree by `Tree::visit()` or `Node::visit()`. The tree is walked depth first
+/// and the children of every node are visited in the order of their names.
+///
+/// This makes it possible to implement passes like culling, serialization or statistics which need
+/// to know when a subtree starts and when it ends.
+pub trait TreeVisitor<T,J,A> where T: node::NodeContent + Clone, J: Clone, A: node::Attribute<J> + Clone{
+    ///Is called when the walk reaches `node` at `depth` (relative to the node the walk started at),
+    /// before any of its children are visited.
+    fn enter(&mut self, node: &mut node::Node<T, J, A>, depth: usize) -> VisitControl;
+    ///Is called when the walk leaves `node`, after all of its children were visited.
+    fn exit(&mut self, _node: &mut node::Node<T, J, A>, _depth: usize){}
+}